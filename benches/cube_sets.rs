@@ -0,0 +1,45 @@
+//! Reboots the reactor from input/input22.txt with both cube set backends and prints how long each
+//! takes. Run with 'cargo bench --bench cube_sets'.
+
+use std::{fs::File, io::BufReader, time::Instant};
+
+use aoc2021::{
+    parser::{self, OnOff},
+    signed_cubes::SignedCubeSet,
+    Cube, CubeSet,
+};
+
+const INPUT_NAME: &str = "input/input22.txt";
+
+fn main() {
+    let steps = parser::reboot_steps(BufReader::new(File::open(INPUT_NAME).unwrap()))
+        .map(|step| {
+            let step = step.unwrap();
+            (step.cube(), step.state == OnOff::On)
+        })
+        .collect::<Vec<_>>();
+    let init_cube = Cube::new(-50..=50, -50..=50, -50..=50);
+
+    let start = Instant::now();
+    let mut cube_set = CubeSet::default();
+    for (cube, on_off) in steps.iter().cloned() {
+        cube_set.add(cube, on_off);
+    }
+    let volumes = (cube_set.volume_intersect(&init_cube), cube_set.volume());
+    println!("CubeSet:       {:?} in {:?}", volumes, start.elapsed());
+
+    let start = Instant::now();
+    let mut signed = SignedCubeSet::default();
+    for (cube, on_off) in steps.iter().cloned() {
+        signed.add(cube, on_off);
+    }
+    let signed_volumes = (signed.volume_intersect(&init_cube), signed.volume());
+    println!(
+        "SignedCubeSet: {:?} in {:?} ({} cubes)",
+        signed_volumes,
+        start.elapsed(),
+        signed.len()
+    );
+
+    assert_eq!(volumes, signed_volumes);
+}
@@ -0,0 +1,43 @@
+//! Finds the largest magnitude of a sum of two numbers among a few thousand generated snailfish
+//! numbers and prints how long it takes. Run with 'cargo bench --bench snailfish'.
+
+use std::time::{Duration, Instant};
+
+use aoc2021::{
+    generators::{random_snailfish_numbers, snailfish_list},
+    snailfish::{max_pair_magnitude, SnNodes, SnailfishRules},
+};
+
+const COUNT: usize = 4000;
+
+fn arenas(numbers: &[aoc2021::SfNumber]) -> Vec<SnNodes> {
+    numbers
+        .iter()
+        .map(|n| SnNodes::try_from(n).unwrap())
+        .collect()
+}
+
+fn main() {
+    let rules = SnailfishRules::default();
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+
+    // small enough for the generator to know the answer
+    let small = snailfish_list(18, 100);
+    let numbers = random_snailfish_numbers(18, 100);
+    let max_pair = max_pair_magnitude(&arenas(&numbers), &rules, threads);
+    assert_eq!(max_pair.map(|(m, _)| m), small.part2);
+
+    let numbers = arenas(&random_snailfish_numbers(18, COUNT));
+    let start = Instant::now();
+    let max_pair = max_pair_magnitude(&numbers, &rules, threads);
+    let elapsed = start.elapsed();
+    println!(
+        "max_pair_magnitude: {:?} of {} numbers in {:?} ({} threads)",
+        max_pair, COUNT, elapsed, threads
+    );
+    assert!(
+        elapsed < Duration::from_secs(10),
+        "{} numbers should take seconds",
+        COUNT
+    );
+}
@@ -0,0 +1,47 @@
+use std::process::ExitCode;
+
+use aoc2021::lint::lint;
+
+const USAGE: &str = "usage: aoc lint <day> <file>";
+
+fn main() -> ExitCode {
+    let args = std::env::args().skip(1).collect::<Vec<_>>();
+    let (day, file) = match &args[..] {
+        [cmd, day, file] if cmd == "lint" => match day.parse::<u32>() {
+            Ok(day) => (day, file),
+            Err(_) => {
+                eprintln!("bad day: {}\n{}", day, USAGE);
+                return ExitCode::from(2);
+            }
+        },
+        _ => {
+            eprintln!("{}", USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let input = match std::fs::read_to_string(file) {
+        Ok(input) => input,
+        Err(e) => {
+            eprintln!("{}: {}", file, e);
+            return ExitCode::from(2);
+        }
+    };
+
+    match lint(day, &input) {
+        None => {
+            eprintln!("no checks for day {}", day);
+            ExitCode::from(2)
+        }
+        Some(problems) if problems.is_empty() => {
+            println!("{}: ok", file);
+            ExitCode::SUCCESS
+        }
+        Some(problems) => {
+            for p in problems {
+                println!("{}:{}", file, p);
+            }
+            ExitCode::FAILURE
+        }
+    }
+}
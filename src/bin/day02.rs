@@ -8,7 +8,7 @@ struct Submarine {
 
 fn main() {
     let s = std::fs::read_to_string("input/input02.txt").unwrap();
-    let commands = parser::submarine_command_list(&s).unwrap().1;
+    let commands = parser::parse_all(parser::submarine_command_list, &s).unwrap();
     println!("{:?}", commands);
     let pos = commands.iter().fold(Vec2::default(), |mut a, c| {
         match c {
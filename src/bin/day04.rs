@@ -2,9 +2,7 @@ use aoc2021::parser;
 
 pub fn main() {
     let s = std::fs::read_to_string("input/input04.txt").unwrap();
-    let (s, input_numbers) = parser::signed_decimal_comma_separated_list(&s).unwrap();
-
-    let mut boards = parser::bingo_board_list(s).unwrap().1;
+    let (input_numbers, mut boards) = parser::parse_all(parser::bingo_game, &s).unwrap();
 
     println!("{:?}", input_numbers);
     println!("{:?}", boards);
@@ -25,7 +25,7 @@ pub fn example() -> (&'static str, Option<Output1>, Option<Output2>) {
 }
 
 fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
-    let (_, line_segments) = parser::line_segment_list(s).unwrap();
+    let line_segments = parser::parse_all(parser::line_segment_list, s).unwrap();
 
     let mut points = HashMap::new();
     for (a, b) in line_segments.iter().filter(|(a, b)| a.y == b.y) {
@@ -103,3 +103,12 @@ fn test() {
     assert_eq!(res1, ref1);
     assert_eq!(res2, ref2);
 }
+
+#[test]
+fn test_generated() {
+    use aoc2021::generators::vent_lines;
+    for seed in 0..5 {
+        let g = vent_lines(seed, 50, 30);
+        assert_eq!(puzzle(&g.input), (g.part1, g.part2));
+    }
+}
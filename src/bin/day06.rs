@@ -1,3 +1,5 @@
+use aoc2021::parser;
+
 type Output1 = i64;
 type Output2 = Output1;
 
@@ -7,8 +9,8 @@ pub fn example() -> (&'static str, Option<Output1>, Option<Output2>) {
 }
 
 fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
-    let input = s.trim().split(',').map(|s| s.parse::<i64>().unwrap());
-    let init_day_count = input.fold([0; 9], |mut acc, n| {
+    let input = parser::parse_all(parser::signed_decimal_comma_separated_list, s).unwrap();
+    let init_day_count = input.into_iter().fold([0; 9], |mut acc, n| {
         acc[n as usize] += 1;
         acc
     });
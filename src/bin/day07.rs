@@ -1,3 +1,5 @@
+use aoc2021::parser;
+
 type Output1 = i64;
 type Output2 = Output1;
 
@@ -7,11 +9,7 @@ pub fn example() -> (&'static str, Option<Output1>, Option<Output2>) {
 }
 
 fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
-    let input = s
-        .trim()
-        .split(',')
-        .map(|s| s.parse::<i64>().unwrap())
-        .collect::<Vec<_>>();
+    let input = parser::parse_all(parser::signed_decimal_comma_separated_list, s).unwrap();
     let max_column = *input.iter().max().unwrap();
     let res1 = (0..=max_column)
         .map(|i| input.iter().map(|c| (*c - i).abs()).sum::<i64>())
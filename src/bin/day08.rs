@@ -36,7 +36,7 @@ pub fn example() -> (&'static str, Option<Output1>, Option<Output2>) {
 }
 
 fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
-    let input = parser::seven_segment_sample_list(s).unwrap().1;
+    let input = parser::parse_all(parser::seven_segment_sample_list, s).unwrap();
     println!("{:?}", input);
     let res1 = input
         .iter()
@@ -105,7 +105,7 @@ fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
     (Some(res1), Some(res2))
 }
 
-fn apply_permutation(i: &str, permutation: &Vec<char>) -> String {
+fn apply_permutation(i: &str, permutation: &[char]) -> String {
     i.chars()
         .map(|c| {
             let i = lowercase_char_to_index(c);
@@ -124,7 +124,7 @@ fn main() {
 #[test]
 fn test() {
     assert_eq!(
-        apply_permutation("acf", &"deafgbc".chars().collect()),
+        apply_permutation("acf", &"deafgbc".chars().collect::<Vec<_>>()),
         "abd"
     );
 
@@ -133,3 +133,12 @@ fn test() {
     assert_eq!(res1, ref1);
     assert_eq!(res2, ref2);
 }
+
+#[test]
+fn test_generated() {
+    use aoc2021::generators::seven_segment;
+    for seed in 0..3 {
+        let g = seven_segment(seed, 10);
+        assert_eq!(puzzle(&g.input), (g.part1, g.part2));
+    }
+}
@@ -1,6 +1,6 @@
-use std::collections::{HashMap, HashSet};
+use std::collections::HashSet;
 
-use aoc2021::Vec2;
+use aoc2021::{read_i64_field, Vec2};
 
 type Output1 = i64;
 type Output2 = Output1;
@@ -17,28 +17,9 @@ pub fn example() -> (&'static str, Option<Output1>, Option<Output2>) {
         Some(1134),
     )
 }
-fn char_to_int(c: char) -> i64 {
-    assert!(c.is_digit(10));
-    (c as u8 - b'0') as i64
-}
 
 fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
-    let height_field: HashMap<Vec2, i64> = s
-        .trim()
-        .lines()
-        .enumerate()
-        .flat_map(|(y, line)| {
-            line.trim().chars().enumerate().map(move |(x, c)| {
-                (
-                    Vec2 {
-                        x: x as i64,
-                        y: y as i64,
-                    },
-                    char_to_int(c),
-                )
-            })
-        })
-        .collect();
+    let height_field = read_i64_field(s);
 
     let mut sum = 0;
     let mut basin_sizes = Vec::new();
@@ -1,3 +1,5 @@
+use aoc2021::parser;
+
 type Output1 = i64;
 type Output2 = Output1;
 
@@ -45,7 +47,8 @@ fn close_score(c: char) -> i64 {
 fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
     let mut res1 = 0;
     let mut close_scores = Vec::new();
-    'outer: for line in s.lines().map(|l| l.trim()) {
+    let lines = parser::parse_all(parser::bracket_line_list, s).unwrap();
+    'outer: for line in lines {
         let mut stack = Vec::new();
         for c in line.chars() {
             if is_opening(c) {
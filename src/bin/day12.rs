@@ -1,7 +1,10 @@
-use std::collections::HashSet;
+use std::collections::BTreeSet;
 
-use aoc2021::bfs_count_paths;
-use multimap::MultiMap;
+use aoc2021::{
+    count_paths_memo,
+    graph::{Direction, Graph, NodeId},
+    parser,
+};
 
 type Output1 = usize;
 type Output2 = Output1;
@@ -59,58 +62,81 @@ start-RW",
     ]
 }
 
-fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
-    let adj = s
-        .trim()
-        .lines()
-        .flat_map(|s| {
-            let (x, y) = s.trim().split_once('-').unwrap();
-            [(x, y), (y, x)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+enum Cave {
+    Small,
+    Big,
+}
+
+impl Cave {
+    fn from_name(name: &str) -> Cave {
+        if name.chars().all(|c| c.is_ascii_lowercase()) {
+            Cave::Small
+        } else {
+            Cave::Big
+        }
+    }
+}
+
+type Caves = Graph<Cave, ()>;
+type State1 = (NodeId, BTreeSet<NodeId>);
+type State2 = (NodeId, bool, BTreeSet<NodeId>);
+
+const START: &str = "start";
+const END: &str = "end";
+
+fn parse_caves(s: &str) -> Caves {
+    let edges = parser::parse_all(parser::edge_list, s).unwrap();
+    Graph::from_edges(Direction::Undirected, edges, Cave::from_name)
+}
+
+fn successors1(caves: &Caves, (cur, visited): &State1) -> Vec<State1> {
+    caves
+        .neighbors(*cur)
+        .filter(|n| !visited.contains(n))
+        .map(|n| {
+            let mut visited = visited.clone();
+            if *caves.node(*cur) == Cave::Small {
+                visited.insert(*cur);
+            }
+            (n, visited)
         })
-        .collect::<MultiMap<_, _>>();
-
-    let start = "start";
-    let end = "end";
-
-    let res1 = bfs_count_paths(
-        (start, HashSet::<&str>::new()),
-        |(cur, visited)| {
-            adj.get_vec(cur)
-                .unwrap()
-                .iter()
-                .cloned()
-                .filter(|n| !visited.contains(n))
-                .map(|n| {
-                    let mut visited = visited.clone();
-                    if cur.chars().all(|c| c.is_ascii_lowercase()) {
-                        visited.insert(cur);
-                    }
-                    (n, visited)
-                })
-                .collect::<Vec<_>>()
-        },
+        .collect()
+}
+
+fn successors2(caves: &Caves, (cur, can_reenter, visited): &State2) -> Vec<State2> {
+    let start = caves.id(START).unwrap();
+    caves
+        .neighbors(*cur)
+        .filter(|n| (*can_reenter && *n != start) || !visited.contains(n))
+        .map(|n| {
+            let mut visited = visited.clone();
+            if *caves.node(*cur) == Cave::Small {
+                visited.insert(*cur);
+            }
+            (n, *can_reenter && !visited.contains(&n), visited)
+        })
+        .collect()
+}
+
+fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
+    let caves = parse_caves(s);
+    let start = caves.id(START).unwrap();
+    let end = caves.id(END).unwrap();
+
+    let res1 = count_paths_memo(
+        (start, BTreeSet::new()),
+        |s| successors1(&caves, s),
         |(node, _)| *node == end,
-    );
-
-    let res2 = bfs_count_paths(
-        (start, true, HashSet::<&str>::new()),
-        |(cur, can_reenter, visited)| {
-            adj.get_vec(cur)
-                .unwrap()
-                .iter()
-                .cloned()
-                .filter(|n| (*can_reenter && *n != start) || !visited.contains(*n))
-                .map(|n| {
-                    let mut visited = visited.clone();
-                    if cur.chars().all(|c| c.is_ascii_lowercase()) {
-                        visited.insert(cur);
-                    }
-                    (n, *can_reenter && !visited.contains(n), visited)
-                })
-                .collect::<Vec<_>>()
-        },
+    )
+    .unwrap();
+
+    let res2 = count_paths_memo(
+        (start, true, BTreeSet::new()),
+        |s| successors2(&caves, s),
         |(node, _, _)| *node == end,
-    );
+    )
+    .unwrap();
 
     (Some(res1), Some(res2))
 }
@@ -129,3 +155,53 @@ fn test() {
         assert_eq!(res2, ref2);
     }
 }
+
+#[test]
+fn test_memo_matches_bfs() {
+    use aoc2021::bfs_count_paths;
+
+    for (example, _, _) in example().iter().cloned() {
+        let caves = parse_caves(example);
+        let start = caves.id(START).unwrap();
+        let end = caves.id(END).unwrap();
+        assert_eq!(
+            count_paths_memo(
+                (start, BTreeSet::new()),
+                |s| successors1(&caves, s),
+                |(node, _)| *node == end
+            ),
+            Ok(bfs_count_paths(
+                (start, BTreeSet::new()),
+                |s| successors1(&caves, s),
+                |(node, _)| *node == end
+            ))
+        );
+        assert_eq!(
+            count_paths_memo(
+                (start, true, BTreeSet::new()),
+                |s| successors2(&caves, s),
+                |(node, _, _)| *node == end
+            ),
+            Ok(bfs_count_paths(
+                (start, true, BTreeSet::new()),
+                |s| successors2(&caves, s),
+                |(node, _, _)| *node == end
+            ))
+        );
+    }
+}
+
+#[test]
+fn test_visit_rules() {
+    use aoc2021::graph::VisitRules;
+
+    for (example, ref1, ref2) in example().iter().cloned() {
+        let caves = parse_caves(example);
+        let start = caves.id(START).unwrap();
+        let end = caves.id(END).unwrap();
+        let small_once = || VisitRules::new(|c: &Cave| (*c == Cave::Small).then_some(1));
+        assert_eq!(Some(caves.paths(start, end, small_once()).count()), ref1);
+        let one_small_twice = small_once().extra_visits(1).no_reentry(start);
+        assert_eq!(Some(caves.paths(start, end, one_small_twice).count()), ref2);
+    }
+}
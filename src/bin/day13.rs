@@ -2,10 +2,9 @@ use std::collections::HashSet;
 
 use aoc2021::{
     dump_bool_field,
-    parser::{coords_and_fold, FoldInstruction},
+    parser::{coords_and_fold, parse_all, FoldInstruction},
     Vec2,
 };
-use itertools::Itertools;
 
 type Output1 = usize;
 type Output2 = Output1;
@@ -61,7 +60,7 @@ fn apply_fold(field: &HashSet<Vec2>, fold: &FoldInstruction) -> HashSet<Vec2> {
 }
 
 fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
-    let (_, (coords, folds)) = coords_and_fold(s).unwrap();
+    let (coords, folds) = parse_all(coords_and_fold, s).unwrap();
     let field = coords.iter().cloned().collect::<HashSet<_>>();
 
     let field1 = apply_fold(&field, &folds[0]);
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use aoc2021::count_occurrences;
+use aoc2021::{count_occurrences, parser};
 use itertools::Itertools;
 
 type Output1 = i64;
@@ -33,26 +33,12 @@ pub fn example() -> &'static [(&'static str, Option<Output1>, Option<Output2>)]
 }
 
 fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
-    //let input = s.trim().split(',').map(|s| s.parse::<i64>().unwrap());
-    let mut lines = s.trim().lines();
-    let template = lines
-        .next()
-        .unwrap()
-        .trim()
+    let (template, rules) = parser::parse_all(parser::polymer_instructions, s).unwrap();
+    let template = template
         .chars()
         .chain(['@']) // ultra cheap: play the annoying 'extend alphabet' card...
         .collect::<Vec<_>>();
-    lines.next().unwrap();
-    let rules = lines
-        .map(|line| {
-            let (a, b) = line.split_once("->").unwrap();
-            let mut a = a.trim().chars();
-            (
-                [a.next().unwrap(), a.next().unwrap()],
-                b.trim().chars().next().unwrap(),
-            )
-        })
-        .collect::<HashMap<_, _>>();
+    let rules = rules.into_iter().collect::<HashMap<_, _>>();
     let mut res1 = None;
     let mut res2 = None;
     let mut dimer_count = count_occurrences(template.windows(2).map(|w| [w[0], w[1]]));
@@ -61,7 +47,7 @@ fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
             .iter()
             .filter_map(|(dimer, count)| {
                 rules.get(dimer).map(|insertion| {
-                    let count = *count as i64;
+                    let count = *count;
                     [
                         // insert X into ab -> + aX, + Xb and - ab
                         (count, [dimer[0], *insertion]),
@@ -1,6 +1,9 @@
-use std::fs::read_dir;
-
-use aoc2021::{i64_field_bounds, read_i64_field, Vec2};
+use aoc2021::{
+    grid::{grid_path, PlainMoves},
+    i64_field_bounds, read_i64_field,
+    search::dijkstra,
+    Vec2,
+};
 
 type Output1 = i64;
 type Output2 = Output1;
@@ -29,16 +32,7 @@ fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
     let (start, end) = i64_field_bounds(&field);
 
     println!("{:?} {:?}", start, end);
-    let dijkstra_res = pathfinding::directed::dijkstra::dijkstra(
-        &start,
-        |f| {
-            f.ortho_neighbors()
-                .iter()
-                .filter_map(|n| field.get(n).map(|score| (*n, *score)))
-                .collect::<Vec<_>>()
-        },
-        |f| *f == end,
-    );
+    let res1 = grid_path(&field, start, end, &PlainMoves).map(|(cost, _)| cost);
 
     let width = end.x - start.x + 1;
     let height = end.y - start.y + 1;
@@ -47,7 +41,7 @@ fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
         y: (end.y + 1) * 5 - 1,
     };
 
-    let dijkstra_res2 = pathfinding::directed::dijkstra::dijkstra(
+    let dijkstra_res2 = dijkstra(
         &start,
         |f| {
             f.ortho_neighbors()
@@ -72,7 +66,7 @@ fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
     .unwrap();
 
     // println!("{:?}", dijkstra_res2.0);
-    (dijkstra_res.map(|(_, score)| score), Some(dijkstra_res2.1))
+    (res1, Some(dijkstra_res2.1))
 }
 
 fn main() {
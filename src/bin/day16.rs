@@ -1,5 +1,3 @@
-use itertools::Itertools;
-
 type Output1 = u64;
 type Output2 = Output1;
 
@@ -101,7 +99,6 @@ trait NibbleReader {
     fn next_bit(&mut self) -> bool;
     fn read_int(&mut self, bits: usize) -> u64;
     fn read_literal(&mut self) -> u64;
-    fn drop_nibble(&mut self);
     fn pos(&self) -> usize;
 }
 
@@ -152,9 +149,6 @@ impl<'a> NibbleReader for NibbleReaderChars<'a> {
         }
         ret
     }
-    fn drop_nibble(&mut self) {
-        self.mask = 0;
-    }
     fn pos(&self) -> usize {
         self.pos
     }
@@ -199,9 +193,17 @@ fn test_bits() {
     let version = r.read_int(3);
     let id = r.read_int(3);
     let lit = r.read_literal();
-    r.drop_nibble();
 
     assert_eq!(version, 6);
     assert_eq!(id, 4);
     assert_eq!(lit, 2021);
 }
+
+#[test]
+fn test_generated() {
+    use aoc2021::generators::bits_packet;
+    for seed in 0..20 {
+        let g = bits_packet(seed, 5);
+        assert_eq!(puzzle(&g.input), (g.part1, g.part2));
+    }
+}
@@ -3,8 +3,9 @@ use aoc2021::Vec2;
 type Output1 = i64;
 type Output2 = Output1;
 
-const INPUT_NAME: &str = "input/inputxx.txt";
-pub fn example() -> &'static [((i64, i64, i64, i64), Option<Output1>, Option<Output2>)] {
+type Example = ((i64, i64, i64, i64), Option<Output1>, Option<Output2>);
+
+pub fn example() -> &'static [Example] {
     &[((20, 30, -10, -5), Some(45), Some(112))]
 }
 
@@ -1,5 +1,7 @@
-use aoc2021::{parser::snailfish_number, SfNumber};
-use itertools::Itertools;
+use aoc2021::{
+    parser::{parse_all, snailfish_number_list},
+    snailfish::{max_pair_magnitude, SnNodes, SnailfishRules},
+};
 
 type Output1 = i64;
 type Output2 = Output1;
@@ -25,33 +27,21 @@ pub fn example() -> &'static [(&'static str, Option<Output1>, Option<Output2>)]
 fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
     // let input = s.trim().split(',').map(|s| s.parse::<i64>().unwrap());
 
-    let mut numbers = s
-        .trim()
-        .lines()
-        .map(|line| snailfish_number(line.trim()).unwrap().1)
-        .collect::<Vec<_>>();
-
-    let sum = numbers
+    let numbers = parse_all(snailfish_number_list, s)
+        .unwrap()
         .iter()
-        .cloned()
-        .reduce(|a, b| {
-            let mut a = SfNumber::Pair(Box::new(a), Box::new(b));
-            a.reduce();
-            a
-        })
+        .map(SnNodes::try_from)
+        .collect::<Result<Vec<_>, _>>()
         .unwrap();
-    println!("{:?}", sum);
 
-    let max_pair = numbers
-        .iter()
-        .permutations(2)
-        .map(|x| {
-            let mut a = SfNumber::Pair(Box::new(x[0].clone()), Box::new(x[1].clone()));
-            a.reduce();
-            a.get_magnitude()
-        })
-        .max();
-    (Some(sum.get_magnitude()), max_pair)
+    let sum = numbers.iter().cloned().reduce(|a, b| a.add(&b)).unwrap();
+
+    let threads = std::thread::available_parallelism().map_or(1, |n| n.get());
+    let max_pair = max_pair_magnitude(&numbers, &SnailfishRules::default(), threads);
+    (
+        Some(sum.magnitude()),
+        max_pair.map(|(magnitude, _)| magnitude),
+    )
 }
 
 fn main() {
@@ -68,3 +58,12 @@ fn test() {
         assert_eq!(res2, ref2);
     }
 }
+
+#[test]
+fn test_generated() {
+    use aoc2021::generators::snailfish_list;
+    for seed in 0..3 {
+        let g = snailfish_list(seed, 6);
+        assert_eq!(puzzle(&g.input), (g.part1, g.part2));
+    }
+}
@@ -1,8 +1,10 @@
 use std::collections::{HashMap, HashSet};
 
-use aoc2021::{parser::scanner_list, Dir3, Vec3, ORIENTATIONS};
+use aoc2021::{
+    parser::{parse_all, scanner_list},
+    Dir3, Vec3, ORIENTATIONS,
+};
 use itertools::Itertools;
-use nom::branch::Permutation;
 
 type Output1 = usize;
 type Output2 = i64;
@@ -31,19 +33,6 @@ impl Scanner {
     }
 }
 
-fn mean_square_dist(a: &[Vec3], b: &[Vec3], trans: Vec3) -> i64 {
-    let num = (a.len() * b.len()) as i64;
-    let mut msd = 0;
-    for x in a.iter() {
-        for y in b.iter() {
-            let v = *x - (*y + trans);
-            let d = v.x * v.x + v.y * v.y + v.z * v.z;
-            msd += d / num;
-        }
-    }
-    msd
-}
-
 fn optimize(a: &[Vec3], b: &[Vec3]) -> Option<Vec3> {
     let aset = a.iter().cloned().collect::<HashSet<_>>();
 
@@ -64,12 +53,11 @@ fn optimize(a: &[Vec3], b: &[Vec3]) -> Option<Vec3> {
 }
 
 fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
-    let (rest, scanners) = scanner_list(s).unwrap();
-    assert!(rest.trim().is_empty());
+    let scanners = parse_all(scanner_list, s).unwrap();
     let scanners = scanners
         .iter()
         .cloned()
-        .map(|(id, probes)| Scanner::new(id, probes))
+        .map(|report| Scanner::new(report.id, report.beacons))
         .collect::<Vec<_>>();
     for s in scanners.iter() {
         println!("{:?}", s);
@@ -333,3 +321,10 @@ pub fn example() -> &'static [(&'static str, Option<Output1>, Option<Output2>)]
         Some(3621),
     )]
 }
+
+#[test]
+fn test_generated() {
+    use aoc2021::generators::scanner_clouds;
+    let (g, _) = scanner_clouds(1, 4);
+    assert_eq!(puzzle(&g.input), (g.part1, g.part2));
+}
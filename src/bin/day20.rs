@@ -1,6 +1,6 @@
-use std::collections::{HashMap, HashSet, VecDeque};
+use std::collections::{HashMap, HashSet};
 
-use aoc2021::{bool_field_bounds, dump_bool_field, Vec2};
+use aoc2021::{bool_field_bounds, dump_bool_field, parser, Vec2};
 
 type Output1 = usize;
 type Output2 = Output1;
@@ -44,34 +44,18 @@ fn check_pixel(
 }
 
 fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
-    // let input = s.trim().split(',').map(|s| s.parse::<i64>().unwrap());
-    let mut lines = s.trim().lines().map(|l| l.trim()).collect::<VecDeque<_>>();
-    let filter = lines[0]
-        .chars()
-        .map(|c| match c {
-            '#' => true,
-            '.' => false,
-            _ => panic!("bad char in filter"),
-        })
-        .collect::<Vec<_>>();
+    let (filter, rows) = parser::parse_all(parser::image_enhancement, s).unwrap();
 
     println!("filter: {:?}", filter);
 
-    let mut y = 0;
-    lines.pop_front();
-    lines.pop_front();
-
     let mut image = HashSet::<Vec2>::new();
-    while let Some(line) = lines.pop_front() {
-        if line.is_empty() {
-            continue;
-        }
-        image.extend(line.chars().enumerate().filter_map(|(x, c)| match c {
-            '#' => Some(Vec2 { x: x as i64, y }),
-            '.' => None,
-            _ => panic!("bad char in image"),
-        }));
-        y += 1;
+    for (y, row) in rows.iter().enumerate() {
+        image.extend(
+            row.iter()
+                .enumerate()
+                .filter(|(_, lit)| **lit)
+                .map(|(x, _)| Vec2::new(x as i64, y as i64)),
+        );
     }
 
     let (min, max) = bool_field_bounds(&image);
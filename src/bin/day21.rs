@@ -1,12 +1,13 @@
 type Output1 = i64;
 type Output2 = Output1;
 
-const INPUT_NAME: &str = "input/inputxx.txt";
-pub fn example() -> &'static [((i64, i64), Option<Output1>, Option<Output2>)] {
+type Example = ((i64, i64), Option<Output1>, Option<Output2>);
+
+pub fn example() -> &'static [Example] {
     &[((4, 8), Some(739785), None)]
 }
 
-fn puzzle((mut pos1, mut pos2): (i64, i64)) -> (Option<Output1>, Option<Output2>) {
+fn puzzle((pos1, pos2): (i64, i64)) -> (Option<Output1>, Option<Output2>) {
     // let input = s.trim().split(',').map(|s| s.parse::<i64>().unwrap());
 
     let mut pos = [pos1, pos2];
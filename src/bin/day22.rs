@@ -1,7 +1,13 @@
-use std::{io::BufRead, ops::RangeInclusive};
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
 
-use aoc2021::{parser, Cube, CubeSet, Overlap};
-use itertools::Itertools;
+use aoc2021::{
+    parser::{self, OnOff},
+    signed_cubes::SignedCubeSet,
+    Cube,
+};
 
 type Output1 = i64;
 type Output2 = Output1;
@@ -110,9 +116,7 @@ pub fn example() -> &'static [(&'static str, Option<Output1>, Option<Output2>)]
     ]
 }
 
-fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
-    let (_, reboot_steps) = parser::reboot_step_list(s).unwrap();
-    println!("{:?}", reboot_steps);
+fn puzzle(input: impl BufRead) -> (Option<Output1>, Option<Output2>) {
     // let mut on_count = 0;
     // for z in -50..=50 {
     //     for y in -50..=50 {
@@ -131,24 +135,16 @@ fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
     //     }
     // }
 
-    let mut cube_set = CubeSet::default();
+    let mut cube_set = SignedCubeSet::default();
 
-    for (on_off, xrange, yrange, zrange) in reboot_steps.iter().cloned() {
-        let add_cube = Cube {
-            xrange,
-            yrange,
-            zrange,
-        };
-        cube_set.add(add_cube, on_off);
+    for step in parser::reboot_steps(input) {
+        let step = step.unwrap();
+        cube_set.add(step.cube(), step.state == OnOff::On);
     }
 
     let vol = cube_set.volume();
 
-    let init_cube = Cube {
-        xrange: -50..=50,
-        yrange: -50..=50,
-        zrange: -50..=50,
-    };
+    let init_cube = Cube::new(-50..=50, -50..=50, -50..=50);
 
     let init_vol = cube_set.volume_intersect(&init_cube);
 
@@ -165,16 +161,25 @@ fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
 }
 
 fn main() {
-    let (res1, res2) = puzzle(&std::fs::read_to_string(INPUT_NAME).unwrap());
+    let (res1, res2) = puzzle(BufReader::new(File::open(INPUT_NAME).unwrap()));
     println!("res1: {:?}", res1);
     println!("res2: {:?}", res2);
 }
 
 #[test]
 fn test() {
-    for (example, ref1, ref2) in example().iter().cloned() {
-        let (res1, res2) = puzzle(example);
-        assert_eq!(res1, ref1);
-        assert_eq!(res2, ref2);
+    for (example, ref1, ref2) in example() {
+        let (res1, res2) = puzzle(example.as_bytes());
+        assert_eq!(res1, *ref1);
+        assert_eq!(res2, *ref2);
+    }
+}
+
+#[test]
+fn test_generated() {
+    use aoc2021::generators::reboot_steps;
+    for seed in 0..3 {
+        let g = reboot_steps(seed, 10, 40);
+        assert_eq!(puzzle(g.input.as_bytes()), (g.part1, g.part2));
     }
 }
@@ -1,5 +1,5 @@
+use aoc2021::search::astar_with_stats;
 use itertools::Itertools;
-use multimap::MultiMap;
 
 type Output1 = i64;
 type Output2 = Output1;
@@ -89,16 +89,11 @@ fn puzzle(s: State) -> (Option<Output1>, Option<Output2>) {
                 {
                     let mut s = s.clone();
                     s.amphipods[i] = AmphipodState::FinalLow(*room);
-                    new_states.push((s, 1 * cost));
+                    new_states.push((s, cost));
                 }
                 // in final high and final low pos is occupied -> do nothing (terminal state)
                 AmphipodState::FinalHigh(room)
-                    if s.amphipods
-                        .iter()
-                        .any(|o| *o == AmphipodState::FinalLow(*room)) =>
-                {
-                    ()
-                }
+                    if s.amphipods.contains(&AmphipodState::FinalLow(*room)) => {}
                 // in final low -> do nothing (terminal state)
                 AmphipodState::FinalLow(_room) => (),
                 AmphipodState::ForeignLow(room) => {
@@ -108,7 +103,7 @@ fn puzzle(s: State) -> (Option<Output1>, Option<Output2>) {
                     }) {
                         let mut s = s.clone();
                         s.amphipods[i] = AmphipodState::ForeignHigh(*room);
-                        new_states.push((s, 1 * cost));
+                        new_states.push((s, cost));
                     }
                 }
                 // in final or foreign high (and none of the other cases matched) -> move to storage
@@ -132,7 +127,8 @@ fn puzzle(s: State) -> (Option<Output1>, Option<Output2>) {
         new_states
     };
 
-    let res = pathfinding::directed::dijkstra::dijkstra(&s, successors, goal);
+    let (res, stats) = astar_with_stats(&s, successors, |_| 0, goal);
+    println!("{:?}", stats);
 
     if let Some((states, cost)) = res {
         println!("res: {}", cost);
@@ -250,7 +246,6 @@ fn test() {
         assert_eq!(res2, ref2);
     }
 }
-const INPUT_NAME: &str = "input/inputxx.txt";
 pub fn example() -> &'static [(State, Option<Output1>, Option<Output2>)] {
     &[
         /*
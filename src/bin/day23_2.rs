@@ -1,5 +1,5 @@
+use aoc2021::search::astar_with_stats;
 use itertools::Itertools;
-use multimap::MultiMap;
 
 type Output1 = i64;
 type Output2 = Output1;
@@ -18,10 +18,10 @@ pub struct State {
 
 fn final_room(i: usize) -> usize {
     match i {
-        0 | 1 | 2 | 3 => 0,
-        4 | 5 | 6 | 7 => 1,
-        8 | 9 | 10 | 11 => 2,
-        12 | 13 | 14 | 15 => 3,
+        0..=3 => 0,
+        4..=7 => 1,
+        8..=11 => 2,
+        12..=15 => 3,
         _ => unreachable!(),
     }
 }
@@ -67,17 +67,16 @@ fn puzzle(s: State) -> (Option<Output1>, Option<Output2>) {
             let cost = cost[i];
             match a {
                 // in storage and can reach high pos in final room (that is not occupied by a foreigner)
-                AmphipodState::Storage(storage_slot) => {
+                AmphipodState::Storage(storage_slot)
                     if can_reach_room(*storage_slot, tr, &storage_occ)
                         && !s.amphipods.iter().any(|o| {
                             *o == AmphipodState::Final(tr, 0)
                                 || matches!(*o, AmphipodState::Foreign(t, _) if t == tr)
-                        })
-                    {
-                        let mut s = s.clone();
-                        s.amphipods[i] = AmphipodState::Final(tr, 0);
-                        new_states.push((s, dist_to_storage(*storage_slot, tr) * cost));
-                    }
+                        }) =>
+                {
+                    let mut s = s.clone();
+                    s.amphipods[i] = AmphipodState::Final(tr, 0);
+                    new_states.push((s, dist_to_storage(*storage_slot, tr) * cost));
                 }
                 // in final and
                 // - no foreign in final
@@ -93,13 +92,13 @@ fn puzzle(s: State) -> (Option<Output1>, Option<Output2>) {
                 {
                     let mut s = s.clone();
                     s.amphipods[i] = AmphipodState::Final(*room, level + 1);
-                    new_states.push((s, 1 * cost));
+                    new_states.push((s, cost));
                 }
 
                 // in final or foreign + 0 and any foreign in room
                 // => move to storage
                 AmphipodState::Foreign(room, pos) | AmphipodState::Final(room, pos)
-                    if !s.amphipods.iter().any(|o|{ 
+                    if !s.amphipods.iter().any(|o|{
                         matches!(*o, AmphipodState::Foreign(r,p) | AmphipodState::Final(r,p) if r == *room && p < *pos )
                     })
                     && s.amphipods
@@ -133,7 +132,7 @@ fn puzzle(s: State) -> (Option<Output1>, Option<Output2>) {
                 {
                     let mut s = s.clone();
                     s.amphipods[i] = AmphipodState::Foreign(*room, level - 1);
-                    new_states.push((s, 1 * cost));
+                    new_states.push((s, cost));
                 }
                 _ => (),
             }
@@ -142,7 +141,8 @@ fn puzzle(s: State) -> (Option<Output1>, Option<Output2>) {
         new_states
     };
 
-    let res = pathfinding::directed::dijkstra::dijkstra(&s, successors, goal);
+    let (res, stats) = astar_with_stats(&s, successors, |_| 0, goal);
+    println!("{:?}", stats);
 
     if let Some((states, cost)) = res {
         println!("res: {}", cost);
@@ -263,7 +263,7 @@ fn main() {
         ],
     };
 
-    let statex =  State {
+    let _statex = State {
         amphipods: [
             // A
             AmphipodState::Final(0, 3),
@@ -300,7 +300,6 @@ fn test() {
         assert_eq!(res2, ref2);
     }
 }
-const INPUT_NAME: &str = "input/inputxx.txt";
 pub fn example() -> &'static [(State, Option<Output1>, Option<Output2>)] {
     &[
         // #############
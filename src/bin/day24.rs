@@ -1,5 +1,4 @@
-use std::collections::HashSet;
-
+use aoc2021::parser;
 use itertools::Itertools;
 use rand::prelude::*;
 
@@ -34,17 +33,14 @@ impl Op {
         }
     }
 
-    pub fn new(line: &str) -> Op {
-        let mut s = line.split_whitespace();
-        let opc = s.next().unwrap();
-        let op1 = s.next().unwrap();
-        let op1n = Op::opn(op1);
+    pub fn new(opc: &str, operands: &[&str]) -> Op {
+        let op1n = Op::opn(operands[0]);
 
         if opc == "inp" {
             return Op::Inp(op1n);
         }
 
-        let op2 = s.next().unwrap();
+        let op2 = operands[1];
         if let Ok(op2) = op2.parse::<i64>() {
             match opc {
                 "add" => Op::AddDir(op1n, op2),
@@ -69,6 +65,14 @@ impl Op {
     }
 }
 
+fn parse_ops(s: &str) -> Vec<Op> {
+    parser::parse_all(parser::alu_program, s)
+        .unwrap()
+        .iter()
+        .map(|(opc, operands)| Op::new(opc, operands))
+        .collect()
+}
+
 struct Alu<'a> {
     pub regs: [i64; 4],
     pub input: Vec<i64>,
@@ -85,7 +89,7 @@ impl<'a> Alu<'a> {
     }
 
     pub fn run(&mut self) {
-        while self.ops.len() != 0 {
+        while !self.ops.is_empty() {
             match self.ops[0] {
                 Op::Inp(r) => self.regs[r as usize] = self.input.pop().unwrap(),
                 Op::Add(a, b) => self.regs[a as usize] += self.regs[b as usize],
@@ -114,11 +118,7 @@ impl<'a> Alu<'a> {
 
 fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
     // let input = s.trim().split(',').map(|s| s.parse::<i64>().unwrap());
-    let ops = s
-        .trim()
-        .lines()
-        .map(|line| Op::new(line.trim()))
-        .collect::<Vec<_>>();
+    let ops = parse_ops(s);
 
     println!("ops: {:?}", ops);
     //    let mut input = [9; 14];
@@ -135,10 +135,10 @@ fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
     let mut min = [1; 14];
     // let max = [9; 14];
     let mut best_input = [1; 14];
-    for i in 0.. {
+    for _i in 0.. {
         let mut alu = Alu::new(&ops[..]);
 
-        let mut new_input = input.clone();
+        let mut new_input = input;
         let pos = loop {
             let pos = rng.gen_range(0..14);
             if min[pos] != 9 {
@@ -175,7 +175,6 @@ fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
             }
 
             let mut a = new_input;
-            let mut b = best_input;
             a.reverse();
 
             if thrs == 0 && a > best_input {
@@ -224,7 +223,8 @@ fn main() {
 
 #[test]
 fn test() {
-    let ops = "inp w
+    let ops = parse_ops(
+        "inp w
     add z w
     mod z 2
     div w 2
@@ -234,11 +234,8 @@ fn test() {
     add x w
     mod x 2
     div w 2
-    mod w 2"
-        .trim()
-        .lines()
-        .map(|line| Op::new(line.trim()))
-        .collect::<Vec<_>>();
+    mod w 2",
+    );
 
     let mut alu = Alu::new(&ops[..]);
     alu.input.push(0b0101);
@@ -1,6 +1,6 @@
 use std::collections::HashSet;
 
-use aoc2021::{bool_field_bounds, Vec2};
+use aoc2021::{bool_field_bounds, cycle::fixed_point, parser, Vec2};
 
 type Output1 = i64;
 type Output2 = Output1;
@@ -26,8 +26,9 @@ fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
     let mut h = HashSet::<Vec2>::new();
     let mut v = HashSet::<Vec2>::new();
 
-    for (y, line) in s.trim().lines().map(|s| s.trim()).enumerate() {
-        for (x, c) in line.chars().enumerate() {
+    let rows = parser::parse_all(parser::sea_cucumber_field, s).unwrap();
+    for (y, row) in rows.into_iter().enumerate() {
+        for (x, c) in row.into_iter().enumerate() {
             let x = x as i64;
             let y = y as i64;
             if c == 'v' {
@@ -41,9 +42,8 @@ fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
     let (_, vmax) = bool_field_bounds(&v);
     let width = hmax.x.max(vmax.x) + 1;
     let height = hmax.y.max(vmax.y) + 1;
-    let mut steps = None;
-    for i in 1.. {
-        let u = h.union(&v).collect::<HashSet<_>>();
+    let step = |(h, v): &(HashSet<Vec2>, HashSet<Vec2>)| {
+        let u = h.union(v).collect::<HashSet<_>>();
 
         let mut newh = HashSet::new();
         for h in h.iter() {
@@ -53,7 +53,7 @@ fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
             };
             newh.insert(if !u.contains(&n) { n } else { *h });
         }
-        let u = newh.union(&v).collect::<HashSet<_>>();
+        let u = newh.union(v).collect::<HashSet<_>>();
         let mut newv = HashSet::new();
 
         for v in v.iter() {
@@ -63,14 +63,10 @@ fn puzzle(s: &str) -> (Option<Output1>, Option<Output2>) {
             };
             newv.insert(if !u.contains(&n) { n } else { *v });
         }
-        if h == newh && v == newv {
-            steps = Some(i);
-            break;
-        }
-
-        h = newh;
-        v = newv;
-    }
+        (newh, newv)
+    };
+    // the first step on which nothing moves is the one applied to the fixed point
+    let steps = fixed_point((h, v), step).map(|(i, _)| i as i64 + 1);
 
     (steps, None)
 }
@@ -0,0 +1,228 @@
+//! Axis aligned boxes of integer points in any number of dimensions, 'Cube' is the 3D one.
+
+use std::{array, fmt, ops::RangeInclusive};
+
+use crate::{overlap_type, Overlap};
+
+/// All points whose coordinate on axis 'i' is in 'ranges[i]'.
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub struct BoxN<const N: usize> {
+    pub ranges: [RangeInclusive<i64>; N],
+}
+
+impl<const N: usize> From<[RangeInclusive<i64>; N]> for BoxN<N> {
+    fn from(ranges: [RangeInclusive<i64>; N]) -> Self {
+        BoxN { ranges }
+    }
+}
+
+/// The volume of a box doesn't fit the type asked for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VolumeOverflow;
+
+impl fmt::Display for VolumeOverflow {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "box volume overflows")
+    }
+}
+
+impl std::error::Error for VolumeOverflow {}
+
+impl BoxN<3> {
+    pub fn new(
+        xrange: RangeInclusive<i64>,
+        yrange: RangeInclusive<i64>,
+        zrange: RangeInclusive<i64>,
+    ) -> Self {
+        BoxN {
+            ranges: [xrange, yrange, zrange],
+        }
+    }
+}
+
+impl<const N: usize> BoxN<N> {
+    pub fn is_empty(&self) -> bool {
+        self.ranges.iter().any(|r| r.is_empty())
+    }
+
+    pub fn contains(&self, point: &[i64; N]) -> bool {
+        self.ranges.iter().zip(point).all(|(r, p)| r.contains(p))
+    }
+
+    pub fn overlaps(&self, other: &BoxN<N>) -> bool {
+        self.ranges
+            .iter()
+            .zip(&other.ranges)
+            .all(|(a, b)| overlap_type(a.clone(), b.clone()) != Overlap::None)
+    }
+
+    /// The box covered by both, if they overlap.
+    pub fn intersection(&self, other: &BoxN<N>) -> Option<BoxN<N>> {
+        let ranges = array::from_fn(|i| {
+            let (a, b) = (&self.ranges[i], &other.ranges[i]);
+            *a.start().max(b.start())..=*a.end().min(b.end())
+        });
+        let common = BoxN { ranges };
+        (!common.is_empty()).then_some(common)
+    }
+
+    /// The smallest box holding both.
+    pub fn hull(&self, other: &BoxN<N>) -> BoxN<N> {
+        let ranges = array::from_fn(|i| {
+            let (a, b) = (&self.ranges[i], &other.ranges[i]);
+            *a.start().min(b.start())..=*a.end().max(b.end())
+        });
+        BoxN { ranges }
+    }
+
+    pub fn split_side(
+        s: RangeInclusive<i64>,
+        r: RangeInclusive<i64>,
+    ) -> (Vec<RangeInclusive<i64>>, Vec<RangeInclusive<i64>>) {
+        let sstart = *s.start();
+        let send = *s.end();
+        let rstart = *r.start();
+        let rend = *r.end();
+
+        let overlap_type = overlap_type(r, s.clone());
+
+        match overlap_type {
+            Overlap::Equals | Overlap::Contains => (vec![s], vec![]),
+            Overlap::Contained => {
+                // Contained:       . . . . . . .
+                //              . . . . . . . . . .
+                assert!(rstart <= rend);
+                assert!(sstart < rstart);
+                assert!(rend < send);
+                (
+                    vec![rstart..=rend],
+                    vec![sstart..=rstart - 1, rend + 1..=send],
+                )
+            }
+            Overlap::Start => {
+                //  Start:      . . . .
+                //                  . .
+                //                  . . . . . .
+                assert!(sstart <= rend);
+                assert!(rend < send);
+                (vec![sstart..=rend], vec![rend + 1..=send])
+            }
+            Overlap::End => {
+                // End:             . . . . . .
+                //                  . .
+                //              . . . .
+                assert!(rstart <= send);
+                assert!(sstart < rstart);
+                (vec![rstart..=send], vec![sstart..=rstart - 1])
+            }
+            Overlap::None => (vec![], vec![s]),
+        }
+    }
+
+    /// Cut 'self' into the boxes inside 'rbox' and the boxes outside of it, None if they don't
+    /// overlap. The cuts go along one axis after the other.
+    pub fn split(&self, rbox: &BoxN<N>) -> Option<(Vec<BoxN<N>>, Vec<BoxN<N>>)> {
+        let mut boxes_in = vec![self.clone()];
+        let mut boxes_out = Vec::new();
+        for axis in 0..N {
+            let mut new_boxes_in = Vec::new();
+            for b in boxes_in {
+                let (in_range, out_range) =
+                    BoxN::<N>::split_side(b.ranges[axis].clone(), rbox.ranges[axis].clone());
+                let with_range = |range| {
+                    let mut b = b.clone();
+                    b.ranges[axis] = range;
+                    b
+                };
+                new_boxes_in.extend(in_range.into_iter().map(with_range));
+                boxes_out.extend(out_range.into_iter().map(with_range));
+            }
+            if new_boxes_in.is_empty() {
+                return None;
+            }
+            boxes_in = new_boxes_in;
+        }
+
+        // boxes near the full i64 range have no u128 volume, and splitting them is fine
+        if cfg!(debug_assertions) {
+            if let Ok(volume) = self.checked_volume() {
+                let parts = boxes_in
+                    .iter()
+                    .chain(&boxes_out)
+                    .map(|b| b.checked_volume());
+                assert_eq!(parts.sum::<Result<u128, _>>(), Ok(volume));
+            }
+        }
+
+        Some((boxes_in, boxes_out))
+    }
+
+    /// The number of points, or an error if that doesn't fit into an u128.
+    pub fn checked_volume(&self) -> Result<u128, VolumeOverflow> {
+        self.ranges.iter().try_fold(1u128, |v, r| {
+            let len = if r.is_empty() {
+                0
+            } else {
+                (*r.end() as i128 - *r.start() as i128 + 1) as u128
+            };
+            v.checked_mul(len).ok_or(VolumeOverflow)
+        })
+    }
+
+    /// The number of points. Panics if that doesn't fit into an u128.
+    pub fn volume_u128(&self) -> u128 {
+        self.checked_volume().unwrap()
+    }
+
+    /// The number of points. Panics if that doesn't fit into an i64.
+    pub fn volume(&self) -> i64 {
+        self.checked_volume()
+            .ok()
+            .and_then(|v| i64::try_from(v).ok())
+            .expect("box volume overflows")
+    }
+}
+
+#[test]
+fn test_boxn() {
+    let rect = BoxN::from([0..=9, 0..=4]);
+    assert_eq!(rect.volume(), 50);
+    let (inside, outside) = rect.split(&BoxN::from([5..=20, -3..=2])).unwrap();
+    assert_eq!(inside, vec![BoxN::from([5..=9, 0..=2])]);
+    assert_eq!(
+        outside,
+        vec![BoxN::from([0..=4, 0..=4]), BoxN::from([5..=9, 3..=4])]
+    );
+    assert_eq!(rect.split(&BoxN::from([10..=20, 0..=4])), None);
+    assert!(rect.contains(&[9, 4]));
+    assert!(!rect.contains(&[10, 4]));
+
+    let tesseract = BoxN::from([-1..=1, -1..=1, -1..=1, -1..=1]);
+    assert_eq!(tesseract.volume(), 81);
+    let corner = BoxN::from([1..=5, 1..=5, 1..=5, 1..=5]);
+    assert!(tesseract.overlaps(&corner));
+    assert_eq!(
+        tesseract.intersection(&corner),
+        Some(BoxN::from([1..=1, 1..=1, 1..=1, 1..=1]))
+    );
+    let (inside, outside) = tesseract.split(&corner).unwrap();
+    assert_eq!(inside.len(), 1);
+    assert_eq!(outside.len(), 4);
+    assert_eq!(
+        outside.iter().map(|b| b.volume()).sum::<i64>(),
+        tesseract.volume() - 1
+    );
+    assert_eq!(
+        tesseract.hull(&corner),
+        BoxN::from([-1..=5, -1..=5, -1..=5, -1..=5])
+    );
+
+    let line = BoxN::from([i64::MIN..=i64::MAX]);
+    assert_eq!(line.checked_volume(), Ok(1 << 64));
+    let plane = BoxN::from([i64::MIN..=i64::MAX, i64::MIN..=i64::MAX]);
+    assert_eq!(plane.checked_volume(), Err(VolumeOverflow));
+    // splitting still works when the volume doesn't fit
+    let (inside, outside) = plane.split(&BoxN::from([0..=1, 0..=1])).unwrap();
+    assert_eq!(inside, vec![BoxN::from([0..=1, 0..=1])]);
+    assert_eq!(outside.len(), 4);
+}
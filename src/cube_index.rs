@@ -0,0 +1,373 @@
+//! An octree over cubes for "which stored cubes overlap this one" queries. A cube is kept in the
+//! nodes it covers completely and in the leaves it overlaps partially, and a leaf that gets too
+//! full is split into its eight octants, so queries only look at cubes in the part of space they
+//! cover.
+//!
+//! The octree bounds grow by doubling as long as they stay inside the i64 range. Cubes that still
+//! don't fit (for example ones reaching near i64::MIN or i64::MAX) go into a flat list that every
+//! query checks.
+
+use crate::Cube;
+
+const LEAF_CAPACITY: usize = 8;
+const MAX_DEPTH: usize = 24;
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    /// Cubes containing all of the node.
+    covering: Vec<usize>,
+    octant: Octant,
+}
+
+#[derive(Debug, Clone)]
+enum Octant {
+    Leaf(Vec<usize>),
+    Split(Box<[Node; 8]>),
+}
+
+impl Default for Octant {
+    fn default() -> Self {
+        Octant::Leaf(Vec::new())
+    }
+}
+
+/// An axis aligned cube of 'size' (a power of two) points per axis starting at 'origin'. The last
+/// point 'origin + (size - 1)' always fits in an i64.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Bounds {
+    origin: [i64; 3],
+    size: i64,
+}
+
+impl Bounds {
+    fn overlaps(&self, cube: &Cube) -> bool {
+        ranges(cube).iter().zip(self.origin).all(|(r, o)| {
+            let end = o + (self.size - 1);
+            *r.start() <= end && *r.end() >= o
+        })
+    }
+
+    fn inside(&self, cube: &Cube) -> bool {
+        ranges(cube).iter().zip(self.origin).all(|(r, o)| {
+            let end = o + (self.size - 1);
+            *r.start() <= o && *r.end() >= end
+        })
+    }
+
+    fn contains(&self, cube: &Cube) -> bool {
+        ranges(cube).iter().zip(self.origin).all(|(r, o)| {
+            let end = o + (self.size - 1);
+            *r.start() >= o && *r.end() <= end
+        })
+    }
+
+    fn octant(&self, i: usize) -> Bounds {
+        let half = self.size / 2;
+        let mut origin = self.origin;
+        for (axis, o) in origin.iter_mut().enumerate() {
+            if i & (1 << axis) != 0 {
+                *o += half;
+            }
+        }
+        Bounds { origin, size: half }
+    }
+}
+
+fn ranges(cube: &Cube) -> &[std::ops::RangeInclusive<i64>; 3] {
+    &cube.ranges
+}
+
+/// Cubes by id, with ids reused after removal.
+#[derive(Debug, Clone)]
+pub struct CubeIndex {
+    cubes: Vec<Option<Cube>>,
+    free: Vec<usize>,
+    len: usize,
+    bounds: Bounds,
+    root: Node,
+    /// Cubes that don't fit in 'bounds'.
+    outside: Vec<usize>,
+}
+
+impl Default for CubeIndex {
+    fn default() -> Self {
+        CubeIndex {
+            cubes: Vec::new(),
+            free: Vec::new(),
+            len: 0,
+            bounds: Bounds {
+                origin: [0; 3],
+                size: 1,
+            },
+            root: Node::default(),
+            outside: Vec::new(),
+        }
+    }
+}
+
+impl CubeIndex {
+    pub fn insert(&mut self, cube: Cube) -> usize {
+        while !self.bounds.contains(&cube) && self.grow(&cube) {}
+        let id = if let Some(id) = self.free.pop() {
+            self.cubes[id] = Some(cube);
+            id
+        } else {
+            self.cubes.push(Some(cube));
+            self.cubes.len() - 1
+        };
+        self.len += 1;
+        self.place(id);
+        id
+    }
+
+    pub fn remove(&mut self, id: usize) -> Option<Cube> {
+        let cube = self.cubes.get_mut(id)?.take()?;
+        if self.bounds.contains(&cube) {
+            remove_from(&mut self.root, self.bounds, id, &cube);
+        } else {
+            self.outside.retain(|&i| i != id);
+        }
+        self.free.push(id);
+        self.len -= 1;
+        Some(cube)
+    }
+
+    pub fn get(&self, id: usize) -> Option<&Cube> {
+        self.cubes.get(id)?.as_ref()
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (usize, &Cube)> {
+        self.cubes
+            .iter()
+            .enumerate()
+            .filter_map(|(id, c)| c.as_ref().map(|c| (id, c)))
+    }
+
+    /// Ids of the stored cubes that overlap 'cube', in increasing order.
+    pub fn overlapping(&self, cube: &Cube) -> Vec<usize> {
+        let mut ids = self
+            .outside
+            .iter()
+            .copied()
+            .filter(|&id| self.cubes[id].as_ref().unwrap().overlaps(cube))
+            .collect::<Vec<_>>();
+        if self.bounds.overlaps(cube) {
+            self.collect(&self.root, self.bounds, cube, &mut ids);
+        }
+        ids.sort_unstable();
+        ids.dedup();
+        ids
+    }
+
+    fn collect(&self, node: &Node, bounds: Bounds, cube: &Cube, ids: &mut Vec<usize>) {
+        ids.extend(&node.covering);
+        match &node.octant {
+            Octant::Leaf(leaf) => ids.extend(
+                leaf.iter()
+                    .filter(|&&id| self.cubes[id].as_ref().unwrap().overlaps(cube)),
+            ),
+            Octant::Split(nodes) => {
+                for (i, node) in nodes.iter().enumerate() {
+                    let bounds = bounds.octant(i);
+                    if bounds.overlaps(cube) {
+                        self.collect(node, bounds, cube, ids);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Put cube 'id' in the octree, or in 'outside' if it doesn't fit the bounds.
+    fn place(&mut self, id: usize) {
+        let cube = self.cubes[id].as_ref().unwrap();
+        if self.bounds.contains(cube) {
+            insert_into(&mut self.root, self.bounds, 0, id, cube, &self.cubes);
+        } else {
+            self.outside.push(id);
+        }
+    }
+
+    /// Double the bounds towards 'cube' and put everything back in. Returns false (and changes
+    /// nothing) if the doubled bounds would leave the i64 range.
+    fn grow(&mut self, cube: &Cube) -> bool {
+        let Some(size) = self.bounds.size.checked_mul(2) else {
+            return false;
+        };
+        let mut origin = self.bounds.origin;
+        for (o, r) in origin.iter_mut().zip(ranges(cube)) {
+            let grown = if *r.start() < *o {
+                o.checked_sub(self.bounds.size)
+            } else {
+                Some(*o)
+            };
+            match grown {
+                Some(g) if g.checked_add(size - 1).is_some() => *o = g,
+                _ => return false,
+            }
+        }
+        self.bounds = Bounds { origin, size };
+        self.root = Node::default();
+        self.outside.clear();
+        for id in 0..self.cubes.len() {
+            if self.cubes[id].is_some() {
+                self.place(id);
+            }
+        }
+        true
+    }
+}
+
+fn insert_into(
+    node: &mut Node,
+    bounds: Bounds,
+    depth: usize,
+    id: usize,
+    cube: &Cube,
+    cubes: &[Option<Cube>],
+) {
+    if bounds.inside(cube) {
+        node.covering.push(id);
+        return;
+    }
+    match &mut node.octant {
+        Octant::Leaf(leaf) => {
+            leaf.push(id);
+            if leaf.len() > LEAF_CAPACITY && depth < MAX_DEPTH && bounds.size > 1 {
+                // only split if it separates something, cubes sharing a face would otherwise be
+                // split along that face down to the smallest octants
+                let separates = (0..8).all(|i| {
+                    let bounds = bounds.octant(i);
+                    leaf.iter()
+                        .filter(|&&id| {
+                            let cube = cubes[id].as_ref().unwrap();
+                            bounds.overlaps(cube) && !bounds.inside(cube)
+                        })
+                        .count()
+                        < leaf.len()
+                });
+                if separates {
+                    let leaf = std::mem::take(leaf);
+                    let mut nodes: [Node; 8] = Default::default();
+                    for (i, node) in nodes.iter_mut().enumerate() {
+                        let bounds = bounds.octant(i);
+                        for &id in &leaf {
+                            let cube = cubes[id].as_ref().unwrap();
+                            if bounds.overlaps(cube) {
+                                insert_into(node, bounds, depth + 1, id, cube, cubes);
+                            }
+                        }
+                    }
+                    node.octant = Octant::Split(Box::new(nodes));
+                }
+            }
+        }
+        Octant::Split(nodes) => {
+            for (i, node) in nodes.iter_mut().enumerate() {
+                let bounds = bounds.octant(i);
+                if bounds.overlaps(cube) {
+                    insert_into(node, bounds, depth + 1, id, cube, cubes);
+                }
+            }
+        }
+    }
+}
+
+fn remove_from(node: &mut Node, bounds: Bounds, id: usize, cube: &Cube) {
+    if bounds.inside(cube) {
+        node.covering.retain(|&i| i != id);
+        return;
+    }
+    match &mut node.octant {
+        Octant::Leaf(leaf) => leaf.retain(|&i| i != id),
+        Octant::Split(nodes) => {
+            for (i, node) in nodes.iter_mut().enumerate() {
+                let bounds = bounds.octant(i);
+                if bounds.overlaps(cube) {
+                    remove_from(node, bounds, id, cube);
+                }
+            }
+            let empty = |n: &Node| {
+                n.covering.is_empty() && matches!(&n.octant, Octant::Leaf(leaf) if leaf.is_empty())
+            };
+            if nodes.iter().all(empty) {
+                node.octant = Octant::Leaf(Vec::new());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_cube_index() {
+    use rand::{rngs::StdRng, Rng, SeedableRng};
+
+    let mut rng = StdRng::seed_from_u64(22);
+    let mut random_cube = |extent: i64| {
+        let mut range = || {
+            let start = rng.gen_range(-extent..extent);
+            start..=start + rng.gen_range(0..extent / 4)
+        };
+        Cube::new(range(), range(), range())
+    };
+
+    let mut index = CubeIndex::default();
+    let mut all = Vec::new();
+    for _ in 0..300 {
+        let cube = random_cube(1000);
+        all.push((index.insert(cube.clone()), cube));
+    }
+    for (id, cube) in all.iter().step_by(3) {
+        assert_eq!(index.remove(*id).as_ref(), Some(cube));
+        assert_eq!(index.remove(*id), None);
+    }
+    all = all
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| i % 3 != 0)
+        .map(|(_, c)| c)
+        .collect();
+    let extra = random_cube(100_000);
+    all.push((index.insert(extra.clone()), extra));
+    assert_eq!(index.len(), all.len());
+
+    for _ in 0..100 {
+        let query = random_cube(1200);
+        let mut expected = all
+            .iter()
+            .filter(|(_, c)| c.overlaps(&query))
+            .map(|(id, _)| *id)
+            .collect::<Vec<_>>();
+        expected.sort_unstable();
+        assert_eq!(index.overlapping(&query), expected);
+    }
+
+    // cubes near the ends of the i64 range don't fit in any octree bounds
+    let mut index = CubeIndex::default();
+    let near = Cube::new(0..=1, 0..=1, 0..=1);
+    let low = Cube::new(i64::MIN..=i64::MIN + 1, 0..=1, 0..=1);
+    let high = Cube::new(0..=1, i64::MAX - 5..=i64::MAX, 0..=1);
+    let all = Cube::new(i64::MIN..=i64::MAX, i64::MIN..=i64::MAX, 0..=0);
+    let ids = [near, low.clone(), high, all].map(|cube| index.insert(cube));
+    assert_eq!(
+        index.overlapping(&Cube::new(0..=0, 0..=0, 0..=0)),
+        vec![ids[0], ids[3]]
+    );
+    assert_eq!(index.overlapping(&low), vec![ids[1], ids[3]]);
+    assert_eq!(
+        index.overlapping(&Cube::new(1..=5, 1..=i64::MAX, 1..=1)),
+        vec![ids[0], ids[2]]
+    );
+    assert_eq!(index.remove(ids[1]), Some(low.clone()));
+    assert_eq!(index.overlapping(&low), vec![ids[3]]);
+    let far = Cube::new(1 << 40..=(1 << 40) + 1, 0..=0, 0..=0);
+    let far_id = index.insert(far.clone());
+    assert_eq!(far_id, ids[1]);
+    assert_eq!(index.overlapping(&far), vec![far_id, ids[3]]);
+}
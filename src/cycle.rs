@@ -0,0 +1,212 @@
+//! Cycle detection for sequences 'init, step(init), step(step(init)), ...'. 'find_cycle' finds
+//! where the sequence starts repeating and how often, and never returns if it doesn't repeat.
+//! 'fixed_point' runs a sequence until it stops changing, 'state_after' jumps to the state after
+//! any number of steps by skipping whole loops.
+
+/// Result of running cycle detection on an iterated state function.
+///
+/// The sequence `init, step(init), step(step(init)), ...` enters a loop after 'start' steps and
+/// repeats every 'period' steps from there on. 'state' is the first state on the loop, i.e. the
+/// state after 'start' steps.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cycle<S> {
+    pub start: usize,
+    pub period: usize,
+    pub state: S,
+}
+
+impl<S> Cycle<S> {
+    /// Map step count 'n' to the smallest step count that reaches the same state.
+    pub fn reduce_steps(&self, n: usize) -> usize {
+        if n < self.start {
+            n
+        } else {
+            self.start + (n - self.start) % self.period
+        }
+    }
+}
+
+fn advance<S, F>(mut s: S, step: &mut F, n: usize) -> S
+where
+    F: FnMut(&S) -> S,
+{
+    for _ in 0..n {
+        s = step(&s);
+    }
+    s
+}
+
+/// Find the cycle in the sequence generated by repeatedly applying 'step' to 'init' (Brent's
+/// algorithm).
+///
+/// Only needs 'Eq' on the state and keeps two states alive at a time. Does not terminate if the
+/// sequence never repeats.
+pub fn find_cycle<S, F>(init: S, mut step: F) -> Cycle<S>
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    let (period, _) = brent_period(init.clone(), &mut step, None);
+    let period = period.expect("unbounded search always finds a period");
+    cycle_start(init, &mut step, period)
+}
+
+/// Phase one of Brent's algorithm: find the period. If 'limit' is given, give up once the hare
+/// has taken that many steps and return the state it reached instead.
+fn brent_period<S, F>(init: S, step: &mut F, limit: Option<usize>) -> (Option<usize>, S)
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    let mut power = 1;
+    let mut period = 1;
+    let mut tortoise = init.clone();
+    let mut hare = step(&init);
+    let mut steps = 1;
+    while tortoise != hare {
+        if limit == Some(steps) {
+            return (None, hare);
+        }
+        if power == period {
+            tortoise = hare.clone();
+            power *= 2;
+            period = 0;
+        }
+        hare = step(&hare);
+        period += 1;
+        steps += 1;
+    }
+    (Some(period), hare)
+}
+
+/// Phase two of Brent's algorithm: with a known period, find the first state on the loop.
+fn cycle_start<S, F>(init: S, step: &mut F, period: usize) -> Cycle<S>
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    let mut tortoise = init.clone();
+    let mut hare = advance(init, step, period);
+    let mut start = 0;
+    while tortoise != hare {
+        tortoise = step(&tortoise);
+        hare = step(&hare);
+        start += 1;
+    }
+    Cycle {
+        start,
+        period,
+        state: tortoise,
+    }
+}
+
+/// Steps 'fixed_point' takes by plain comparison before it starts looking for longer loops.
+const FIXED_POINT_DIRECT_STEPS: usize = 1 << 12;
+
+/// Apply 'step' until the state no longer changes.
+///
+/// Returns the number of steps it took to reach the fixed point together with the fixed point
+/// itself, or 'None' if the sequence runs into a loop with a period greater than one.
+///
+/// Each state is only compared with its successor, so this costs one 'step' call per step. Only
+/// after 'FIXED_POINT_DIRECT_STEPS' steps does it fall back to 'find_cycle' so that longer loops
+/// still terminate.
+pub fn fixed_point<S, F>(init: S, mut step: F) -> Option<(usize, S)>
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    let mut s = init;
+    for n in 0..FIXED_POINT_DIRECT_STEPS {
+        let next = step(&s);
+        if next == s {
+            return Some((n, s));
+        }
+        s = next;
+    }
+    let cycle = find_cycle(s, step);
+    if cycle.period == 1 {
+        Some((FIXED_POINT_DIRECT_STEPS + cycle.start, cycle.state))
+    } else {
+        None
+    }
+}
+
+/// Compute the state after applying 'step' 'n' times to 'init'.
+///
+/// If the sequence repeats before 'n' steps, the whole loops are skipped, so this also works for
+/// huge 'n' as long as the sequence becomes periodic early enough.
+pub fn state_after<S, F>(init: S, mut step: F, n: usize) -> S
+where
+    S: Clone + Eq,
+    F: FnMut(&S) -> S,
+{
+    if n == 0 {
+        return init;
+    }
+    match brent_period(init.clone(), &mut step, Some(n)) {
+        (Some(period), _) => {
+            // the hare already went around the loop at least once, so n >= cycle.start
+            let cycle = cycle_start(init, &mut step, period);
+            let remaining = cycle.reduce_steps(n) - cycle.start;
+            advance(cycle.state, &mut step, remaining)
+        }
+        (None, s) => s,
+    }
+}
+
+#[cfg(test)]
+fn naive_sequence(init: u64, step: fn(&u64) -> u64, n: usize) -> Vec<u64> {
+    let mut v = vec![init];
+    for _ in 0..n {
+        v.push(step(v.last().unwrap()));
+    }
+    v
+}
+
+#[test]
+fn test_find_cycle() {
+    let step = |x: &u64| (x * x + 1) % 255;
+    let seq = naive_sequence(3, step, 100);
+    let cycle = find_cycle(3, step);
+
+    // reference: first repeated state via exhaustive search
+    let (start, period) = (0..seq.len())
+        .find_map(|j| (0..j).find(|i| seq[*i] == seq[j]).map(|i| (i, j - i)))
+        .unwrap();
+    assert_eq!(cycle.start, start);
+    assert_eq!(cycle.period, period);
+    assert_eq!(cycle.state, seq[start]);
+
+    for (n, s) in seq.iter().enumerate() {
+        assert_eq!(state_after(3, step, n), *s);
+    }
+    assert_eq!(
+        state_after(3, step, 1_000_000_000_000),
+        seq[cycle.reduce_steps(1_000_000_000_000)]
+    );
+}
+
+#[test]
+fn test_fixed_point() {
+    assert_eq!(fixed_point(100u64, |x| x / 2), Some((7, 0)));
+    assert_eq!(fixed_point(5u64, |x| *x), Some((0, 5)));
+    assert_eq!(fixed_point(0u64, |x| (x + 1) % 3), None);
+    // past the direct steps, the fallback still counts from 'init'
+    let n = FIXED_POINT_DIRECT_STEPS as u64 + 10;
+    assert_eq!(fixed_point(0u64, |x| (x + 1).min(n)), Some((n as usize, n)));
+    assert_eq!(
+        fixed_point(0u64, |x| if *x < n { x + 1 } else { n + (x - n + 1) % 2 }),
+        None
+    );
+    let mut calls = 0;
+    assert_eq!(
+        fixed_point(100u64, |x| {
+            calls += 1;
+            x / 2
+        }),
+        Some((7, 0))
+    );
+    assert_eq!(calls, 8);
+    assert_eq!(state_after(100u64, |x| x / 2, usize::MAX), 0);
+}
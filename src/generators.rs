@@ -0,0 +1,626 @@
+//! Seeded random inputs in the puzzle formats, for stress tests and for checking the solvers
+//! against answers that are known by construction.
+
+use std::collections::{BTreeSet, HashMap, HashSet};
+
+use itertools::Itertools;
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+use crate::{
+    parser::{OnOff, RebootStep, ScannerReport, XYZ},
+    BingoBoard, Dir3, SfNumber, Vec2, Vec3, ORIENTATIONS,
+};
+
+/// A generated puzzle input with the expected answers, where those are cheap to compute without
+/// the actual solver (same shape as the 'example()' of the binaries).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Generated<A1, A2 = A1> {
+    pub input: String,
+    pub part1: Option<A1>,
+    pub part2: Option<A2>,
+}
+
+/// Day 4: drawn numbers and 'board_count' boards. The answers are the scores of the first and the
+/// last board to win.
+pub fn bingo(seed: u64, board_count: usize) -> Generated<i64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let pool = (0..100).collect::<Vec<i64>>();
+    let mut numbers = pool.clone();
+    numbers.shuffle(&mut rng);
+
+    let mut boards = (0..board_count)
+        .map(|_| {
+            let cells = pool.choose_multiple(&mut rng, 25).cloned().collect_vec();
+            BingoBoard::new(cells.chunks(5).map(|row| row.to_vec()).collect())
+        })
+        .collect_vec();
+
+    let input = format!(
+        "{}\n\n{}\n",
+        numbers.iter().join(","),
+        boards.iter().join("\n\n")
+    );
+
+    let mut scores = Vec::new();
+    let mut won = vec![false; boards.len()];
+    for n in numbers {
+        for (board, won) in boards.iter_mut().zip(won.iter_mut()) {
+            if !*won && board.apply(n) {
+                *won = true;
+                scores.push(board.count_remaining() * n);
+            }
+        }
+    }
+    Generated {
+        input,
+        part1: scores.first().copied(),
+        part2: scores.last().copied(),
+    }
+}
+
+/// Day 5: 'count' horizontal, vertical and diagonal lines in a 'size' x 'size' area. The answers
+/// are the number of points covered more than once, without and with the diagonals.
+pub fn vent_lines(seed: u64, count: usize, size: i64) -> Generated<usize> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut segments = Vec::new();
+    while segments.len() < count {
+        let a = Vec2::new(rng.gen_range(0..size), rng.gen_range(0..size));
+        let b = match rng.gen_range(0..3) {
+            0 => Vec2::new(rng.gen_range(0..size), a.y),
+            1 => Vec2::new(a.x, rng.gen_range(0..size)),
+            _ => {
+                let d = rng.gen_range(0..size);
+                Vec2::new(
+                    a.x + if rng.gen() { d } else { -d },
+                    a.y + if rng.gen() { d } else { -d },
+                )
+            }
+        };
+        if a != b && (0..size).contains(&b.x) && (0..size).contains(&b.y) {
+            segments.push((a, b));
+        }
+    }
+
+    let input = segments
+        .iter()
+        .map(|(a, b)| format!("{} -> {}\n", a, b))
+        .collect();
+
+    let mut straight = HashMap::new();
+    let mut all = HashMap::new();
+    for (a, b) in &segments {
+        let step = Vec2::new((b.x - a.x).signum(), (b.y - a.y).signum());
+        let is_straight = step.x == 0 || step.y == 0;
+        let mut p = *a;
+        loop {
+            *all.entry(p).or_insert(0) += 1;
+            if is_straight {
+                *straight.entry(p).or_insert(0) += 1;
+            }
+            if p == *b {
+                break;
+            }
+            p = Vec2::new(p.x + step.x, p.y + step.y);
+        }
+    }
+    Generated {
+        input,
+        part1: Some(straight.values().filter(|n| **n >= 2).count()),
+        part2: Some(all.values().filter(|n| **n >= 2).count()),
+    }
+}
+
+/// Segments of the digits 0-9 on an unscrambled display.
+const DIGIT_SEGMENTS: [&str; 10] = [
+    "abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg",
+];
+
+/// Day 8: 'count' displays with randomly crossed wires. The answers are the number of 1, 4, 7 and
+/// 8 digits in the outputs and the sum of the output values.
+pub fn seven_segment(seed: u64, count: usize) -> Generated<usize> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut input = String::new();
+    let (mut easy_digits, mut sum) = (0, 0);
+    for _ in 0..count {
+        let mut wiring = "abcdefg".chars().collect_vec();
+        wiring.shuffle(&mut rng);
+        let scramble = |digit: usize, rng: &mut StdRng| {
+            let mut segments = DIGIT_SEGMENTS[digit]
+                .chars()
+                .map(|c| wiring[crate::lowercase_char_to_index(c)])
+                .collect_vec();
+            segments.shuffle(rng);
+            segments.into_iter().collect::<String>()
+        };
+
+        let mut patterns = (0..10).collect_vec();
+        patterns.shuffle(&mut rng);
+        let patterns = patterns
+            .into_iter()
+            .map(|d| scramble(d, &mut rng))
+            .collect_vec();
+        let digits = (0..4).map(|_| rng.gen_range(0..10)).collect_vec();
+        let outputs = digits.iter().map(|d| scramble(*d, &mut rng)).collect_vec();
+
+        easy_digits += digits.iter().filter(|d| [1, 4, 7, 8].contains(*d)).count();
+        sum += digits.iter().fold(0, |acc, d| acc * 10 + d);
+        input += &format!("{} | {}\n", patterns.join(" "), outputs.join(" "));
+    }
+    Generated {
+        input,
+        part1: Some(easy_digits),
+        part2: Some(sum),
+    }
+}
+
+/// Day 12: a cave system with 'small' small caves, 'big' big caves and 'edge_count' tunnels
+/// besides the ones at 'start' and 'end'. Big caves are never connected to each other, so the
+/// number of paths is finite, but counting them is the expensive part of the puzzle, so no
+/// answers are given. Panics if there are no caves at all.
+pub fn cave_graph(seed: u64, small: usize, big: usize, edge_count: usize) -> Generated<usize> {
+    assert!(small + big > 0, "a cave system needs at least one cave");
+    let mut rng = StdRng::seed_from_u64(seed);
+    // "a".."z", "aa".."zz", "aaa".. so there are enough names for any size, minus the two that
+    // are taken
+    let names = (1..).map(|mut i: usize| {
+        let mut name = Vec::new();
+        while i > 0 {
+            i -= 1;
+            name.push(b'a' + (i % 26) as u8);
+            i /= 26;
+        }
+        name.reverse();
+        String::from_utf8(name).unwrap()
+    });
+    let small_caves = names
+        .clone()
+        .filter(|n| n != "start" && n != "end")
+        .take(small)
+        .collect_vec();
+    let big_caves = names.take(big).map(|n| n.to_uppercase()).collect_vec();
+    let caves = small_caves.iter().chain(big_caves.iter()).collect_vec();
+
+    let mut edges = Vec::new();
+    for end in ["start", "end"] {
+        edges.push((end.to_string(), caves.choose(&mut rng).unwrap().to_string()));
+    }
+    let mut seen = HashSet::new();
+    let mut attempts = 0;
+    while edges.len() < edge_count + 2 && attempts < 100 * edge_count {
+        attempts += 1;
+        let (a, b) = (
+            caves.choose(&mut rng).unwrap(),
+            caves.choose(&mut rng).unwrap(),
+        );
+        let is_big = |n: &str| n.chars().all(|c| c.is_ascii_uppercase());
+        if a == b || (is_big(a) && is_big(b)) || !seen.insert((a.min(b), a.max(b))) {
+            continue;
+        }
+        edges.push((a.to_string(), b.to_string()));
+    }
+
+    Generated {
+        input: edges
+            .iter()
+            .map(|(a, b)| format!("{}-{}\n", a, b))
+            .collect(),
+        part1: None,
+        part2: None,
+    }
+}
+
+fn random_snailfish_number(rng: &mut StdRng, depth: usize) -> SfNumber {
+    if depth == 4 || (depth > 0 && rng.gen_bool(0.3)) {
+        SfNumber::Number(rng.gen_range(0..10))
+    } else {
+        SfNumber::Pair(
+            Box::new(random_snailfish_number(rng, depth + 1)),
+            Box::new(random_snailfish_number(rng, depth + 1)),
+        )
+    }
+}
+
+/// Day 18: the numbers of 'snailfish_list' without the answers, which take quadratic time to work
+/// out. For lists too long to check by brute force.
+pub fn random_snailfish_numbers(seed: u64, count: usize) -> Vec<SfNumber> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    (0..count)
+        .map(|_| random_snailfish_number(&mut rng, 0))
+        .collect_vec()
+}
+
+/// Day 18: 'count' reduced snailfish numbers. The answers are the magnitude of the sum and the
+/// largest magnitude of the sum of any two different numbers.
+pub fn snailfish_list(seed: u64, count: usize) -> Generated<i64> {
+    let numbers = random_snailfish_numbers(seed, count);
+    let sum: Option<SfNumber> = numbers.iter().sum();
+    let max_pair = numbers
+        .iter()
+        .permutations(2)
+        .map(|p| (p[0].clone() + p[1].clone()).get_magnitude())
+        .max();
+    Generated {
+        input: numbers.iter().map(|n| format!("{}\n", n)).collect(),
+        part1: sum.map(|s| s.get_magnitude()),
+        part2: max_pair,
+    }
+}
+
+/// Where a generated scanner is and how it is turned: a beacon at 'local' in its report is at
+/// 'local.permute(&orientation) + position' as seen from scanner 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScannerTransform {
+    pub orientation: (Dir3, Dir3, Dir3),
+    pub position: Vec3,
+}
+
+const SCANNER_RANGE: i64 = 1000;
+
+fn random_in_box(rng: &mut StdRng, min: Vec3, max: Vec3) -> Vec3 {
+    Vec3::new(
+        rng.gen_range(min.x..=max.x),
+        rng.gen_range(min.y..=max.y),
+        rng.gen_range(min.z..=max.z),
+    )
+}
+
+/// Day 19: 'scanner_count' scanner reports. Every scanner shares at least 12 beacons with one of
+/// the scanners before it. The answers are the number of beacons and the largest manhattan
+/// distance between two scanners, the transforms are the ground truth for each scanner.
+pub fn scanner_clouds(
+    seed: u64,
+    scanner_count: usize,
+) -> (Generated<usize, i64>, Vec<ScannerTransform>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let identity = ORIENTATIONS[0];
+    let mut transforms = vec![ScannerTransform {
+        orientation: identity,
+        position: Vec3::new(0, 0, 0),
+    }];
+    let mut beacons = BTreeSet::new();
+    let r = Vec3::new(SCANNER_RANGE, SCANNER_RANGE, SCANNER_RANGE);
+
+    for i in 0..scanner_count {
+        if i > 0 {
+            let parent = transforms[rng.gen_range(0..i)].position;
+            let d = 1200;
+            let position =
+                parent + random_in_box(&mut rng, Vec3::new(-d, -d, -d), Vec3::new(d, d, d));
+            let (lo, hi) = (parent - r, parent + r);
+            let (lo, hi) = (
+                Vec3::new(
+                    lo.x.max(position.x - r.x),
+                    lo.y.max(position.y - r.y),
+                    lo.z.max(position.z - r.z),
+                ),
+                Vec3::new(
+                    hi.x.min(position.x + r.x),
+                    hi.y.min(position.y + r.y),
+                    hi.z.min(position.z + r.z),
+                ),
+            );
+            while beacons
+                .iter()
+                .filter(|b| in_range(**b, position) && in_range(**b, parent))
+                .count()
+                < 12
+            {
+                beacons.insert(random_in_box(&mut rng, lo, hi));
+            }
+            transforms.push(ScannerTransform {
+                orientation: *ORIENTATIONS.choose(&mut rng).unwrap(),
+                position,
+            });
+        }
+        let position = transforms[i].position;
+        for _ in 0..10 {
+            beacons.insert(random_in_box(&mut rng, position - r, position + r));
+        }
+    }
+
+    let reports = transforms
+        .iter()
+        .enumerate()
+        .map(|(id, t)| {
+            let inverse = ORIENTATIONS
+                .iter()
+                .find(|o| {
+                    Vec3::new(1, 2, 3).permute(&t.orientation).permute(o) == Vec3::new(1, 2, 3)
+                })
+                .unwrap();
+            let mut seen = beacons
+                .iter()
+                .filter(|b| in_range(**b, t.position))
+                .map(|b| (*b - t.position).permute(inverse))
+                .collect_vec();
+            seen.shuffle(&mut rng);
+            ScannerReport {
+                id: id as i64,
+                beacons: seen,
+            }
+        })
+        .collect_vec();
+
+    let max_dist = transforms
+        .iter()
+        .tuple_combinations()
+        .map(|(a, b)| a.position.manhattan_dist(&b.position))
+        .max()
+        .unwrap_or(0);
+    (
+        Generated {
+            input: reports.iter().join("\n\n") + "\n",
+            part1: Some(beacons.len()),
+            part2: Some(max_dist),
+        },
+        transforms,
+    )
+}
+
+fn in_range(beacon: Vec3, scanner: Vec3) -> bool {
+    let d = beacon - scanner;
+    d.x.abs() <= SCANNER_RANGE && d.y.abs() <= SCANNER_RANGE && d.z.abs() <= SCANNER_RANGE
+}
+
+/// Day 22: 'count' reboot steps with coordinates in -'extent'..='extent'. The first answer (cubes
+/// on in the -50..=50 region) is counted cube by cube. The second one is only given if everything
+/// is inside that region anyway.
+pub fn reboot_steps(seed: u64, count: usize, extent: i64) -> Generated<i64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let steps = (0..count)
+        .map(|i| {
+            let mut range = || {
+                let (a, b) = (
+                    rng.gen_range(-extent..=extent),
+                    rng.gen_range(-extent..=extent),
+                );
+                a.min(b)..=a.max(b)
+            };
+            let ranges = [range(), range(), range()];
+            RebootStep {
+                state: if i == 0 || rng.gen_bool(0.6) {
+                    OnOff::On
+                } else {
+                    OnOff::Off
+                },
+                axes: XYZ,
+                ranges,
+            }
+        })
+        .collect_vec();
+
+    const INIT: i64 = 50;
+    const SIDE: usize = 2 * INIT as usize + 1;
+    let mut cubes = vec![false; SIDE * SIDE * SIDE];
+    let index = |v: i64| (v + INIT) as usize;
+    for step in &steps {
+        let [xs, ys, zs] = step
+            .ranges
+            .clone()
+            .map(|r| (*r.start()).max(-INIT)..=(*r.end()).min(INIT));
+        for z in zs {
+            for y in ys.clone() {
+                for x in xs.clone() {
+                    cubes[(index(z) * SIDE + index(y)) * SIDE + index(x)] = step.state == OnOff::On;
+                }
+            }
+        }
+    }
+    let init_count = cubes.iter().filter(|c| **c).count() as i64;
+
+    Generated {
+        input: steps.iter().map(|s| format!("{}\n", s)).collect(),
+        part1: Some(init_count),
+        part2: (extent <= INIT).then_some(init_count),
+    }
+}
+
+enum Packet {
+    Literal(u64, u64),
+    Operator(u64, u64, bool, Vec<Packet>),
+}
+
+impl Packet {
+    fn version_sum(&self) -> u64 {
+        match self {
+            Packet::Literal(version, _) => *version,
+            Packet::Operator(version, _, _, children) => {
+                version + children.iter().map(Packet::version_sum).sum::<u64>()
+            }
+        }
+    }
+
+    fn value(&self) -> u64 {
+        match self {
+            Packet::Literal(_, value) => *value,
+            Packet::Operator(_, type_id, _, children) => {
+                let values = children.iter().map(Packet::value).collect_vec();
+                match type_id {
+                    0 => values.iter().sum(),
+                    1 => values.iter().product(),
+                    2 => *values.iter().min().unwrap(),
+                    3 => *values.iter().max().unwrap(),
+                    5 => (values[0] > values[1]) as u64,
+                    6 => (values[0] < values[1]) as u64,
+                    _ => (values[0] == values[1]) as u64,
+                }
+            }
+        }
+    }
+
+    fn write_bits(&self, bits: &mut Vec<bool>) {
+        fn push(bits: &mut Vec<bool>, value: u64, len: usize) {
+            bits.extend((0..len).rev().map(|i| value >> i & 1 == 1));
+        }
+        match self {
+            Packet::Literal(version, value) => {
+                push(bits, *version, 3);
+                push(bits, 4, 3);
+                let groups = (0..16)
+                    .rev()
+                    .skip_while(|i| i > &0 && value >> (4 * i) == 0);
+                let groups = groups.collect_vec();
+                for (n, i) in groups.iter().enumerate() {
+                    bits.push(n + 1 < groups.len());
+                    push(bits, value >> (4 * i) & 0xf, 4);
+                }
+            }
+            Packet::Operator(version, type_id, by_count, children) => {
+                push(bits, *version, 3);
+                push(bits, *type_id, 3);
+                bits.push(*by_count);
+                let mut content = Vec::new();
+                children.iter().for_each(|c| c.write_bits(&mut content));
+                if *by_count {
+                    push(bits, children.len() as u64, 11);
+                } else {
+                    push(bits, content.len() as u64, 15);
+                }
+                bits.extend(content);
+            }
+        }
+    }
+}
+
+fn random_packet(rng: &mut StdRng, depth: usize) -> Packet {
+    let version = rng.gen_range(0..8);
+    if depth == 0 || rng.gen_bool(0.3) {
+        return Packet::Literal(version, rng.gen_range(0..=0xfff));
+    }
+    let mut type_id = *[0, 1, 2, 3, 5, 6, 7].choose(rng).unwrap();
+    let count = if type_id >= 5 {
+        2
+    } else {
+        rng.gen_range(1..=3)
+    };
+    let children = (0..count)
+        .map(|_| random_packet(rng, depth - 1))
+        .collect_vec();
+    let values = children.iter().map(Packet::value).collect_vec();
+    // keep the value in u64: fall back to 'minimum' if a sum or product would overflow
+    let overflows = match type_id {
+        0 => values
+            .iter()
+            .try_fold(0u64, |a, v| a.checked_add(*v))
+            .is_none(),
+        1 => values
+            .iter()
+            .try_fold(1u64, |a, v| a.checked_mul(*v))
+            .is_none(),
+        _ => false,
+    };
+    if overflows {
+        type_id = 2;
+    }
+    Packet::Operator(version, type_id, rng.gen(), children)
+}
+
+/// Day 16: a hex encoded BITS transmission with packets nested up to 'max_depth' levels. The
+/// answers are the sum of all versions and the value of the outermost packet.
+pub fn bits_packet(seed: u64, max_depth: usize) -> Generated<u64> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let packet = random_packet(&mut rng, max_depth);
+    let mut bits = Vec::new();
+    packet.write_bits(&mut bits);
+    while bits.len() % 4 != 0 {
+        bits.push(false);
+    }
+    let input = bits
+        .chunks(4)
+        .map(|nibble| {
+            let n = nibble.iter().fold(0, |acc, b| acc << 1 | *b as u32);
+            std::char::from_digit(n, 16).unwrap().to_ascii_uppercase()
+        })
+        .collect();
+    Generated {
+        input,
+        part1: Some(packet.version_sum()),
+        part2: Some(packet.value()),
+    }
+}
+
+#[test]
+fn test_generators_deterministic() {
+    assert_eq!(bingo(1, 3), bingo(1, 3));
+    assert_ne!(bingo(1, 3).input, bingo(2, 3).input);
+    assert_eq!(vent_lines(5, 20, 10), vent_lines(5, 20, 10));
+    assert_eq!(scanner_clouds(7, 3), scanner_clouds(7, 3));
+    assert_eq!(bits_packet(3, 3), bits_packet(3, 3));
+}
+
+#[test]
+fn test_generated_inputs_parse() {
+    use crate::parser::{
+        bingo_game, edge_list, line_segment_list, parse_all, reboot_step_list, scanner_list,
+        seven_segment_sample_list, snailfish_number_list,
+    };
+
+    let g = bingo(1, 3);
+    let (numbers, boards) = parse_all(bingo_game, &g.input).unwrap();
+    assert_eq!((numbers.len(), boards.len()), (100, 3));
+    assert!(g.part1.is_some() && g.part2.is_some());
+
+    assert_eq!(
+        parse_all(line_segment_list, &vent_lines(2, 50, 20).input)
+            .unwrap()
+            .len(),
+        50
+    );
+    assert_eq!(
+        parse_all(seven_segment_sample_list, &seven_segment(3, 5).input)
+            .unwrap()
+            .len(),
+        5
+    );
+    assert_eq!(
+        parse_all(edge_list, &cave_graph(4, 5, 2, 8).input)
+            .unwrap()
+            .len(),
+        10
+    );
+    // more caves than there are two letter names
+    let big_input = cave_graph(4, 800, 800, 3000).input;
+    let big_graph = parse_all(edge_list, &big_input).unwrap();
+    assert_eq!(big_graph.len(), 3002);
+    assert!(big_graph.iter().all(|(a, b)| a != b
+        && ![a, b]
+            .iter()
+            .all(|n| n.chars().all(|c| c.is_ascii_uppercase()))));
+    assert_eq!(
+        parse_all(snailfish_number_list, &snailfish_list(5, 4).input)
+            .unwrap()
+            .len(),
+        4
+    );
+    assert_eq!(
+        parse_all(reboot_step_list, &reboot_steps(6, 10, 40).input)
+            .unwrap()
+            .len(),
+        10
+    );
+
+    let (g, transforms) = scanner_clouds(7, 4);
+    let reports = parse_all(scanner_list, &g.input).unwrap();
+    assert_eq!(reports.len(), 4);
+    // every beacon is where the ground truth says it is
+    let mut beacons = HashSet::new();
+    for (report, t) in reports.iter().zip(transforms.iter()) {
+        beacons.extend(
+            report
+                .beacons
+                .iter()
+                .map(|b| b.permute(&t.orientation) + t.position),
+        );
+    }
+    assert_eq!(Some(beacons.len()), g.part1);
+}
+
+#[test]
+fn test_bits_packet() {
+    // 2021 needs three groups of five bits after the six header bits
+    let mut bits = Vec::new();
+    Packet::Literal(6, 2021).write_bits(&mut bits);
+    assert_eq!(bits.len(), 21);
+    let g = bits_packet(9, 4);
+    assert!(g.input.chars().all(|c| c.is_ascii_hexdigit()));
+}
@@ -0,0 +1,656 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt::Write,
+};
+
+pub type NodeId = usize;
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Direction {
+    Directed,
+    Undirected,
+}
+
+/// Graph with named nodes.
+///
+/// Node names are interned: every name maps to a dense 'NodeId', which is what all other methods
+/// work with. Each node carries an attribute of type 'N' and each edge one of type 'E'. In an
+/// undirected graph every edge is stored in both directions.
+#[derive(Debug, Clone)]
+pub struct Graph<N, E> {
+    direction: Direction,
+    names: Vec<String>,
+    ids: HashMap<String, NodeId>,
+    nodes: Vec<N>,
+    edges: Vec<Vec<(NodeId, E)>>,
+}
+
+impl<N, E> Graph<N, E> {
+    pub fn new(direction: Direction) -> Self {
+        Graph {
+            direction,
+            names: Vec::new(),
+            ids: HashMap::new(),
+            nodes: Vec::new(),
+            edges: Vec::new(),
+        }
+    }
+
+    /// Build a graph from a list of '(from, to)' name pairs, e.g. the output of
+    /// 'parser::edge_list'. 'node_attr' is called once for every distinct node name.
+    pub fn from_edges<'a, I, FA>(direction: Direction, edges: I, mut node_attr: FA) -> Self
+    where
+        I: IntoIterator<Item = (&'a str, &'a str)>,
+        FA: FnMut(&str) -> N,
+        E: Default + Clone,
+    {
+        let mut graph = Graph::new(direction);
+        for (a, b) in edges {
+            let a = graph.intern(a, &mut node_attr);
+            let b = graph.intern(b, &mut node_attr);
+            graph.add_edge(a, b, E::default());
+        }
+        graph
+    }
+
+    fn intern<FA: FnMut(&str) -> N>(&mut self, name: &str, node_attr: &mut FA) -> NodeId {
+        match self.ids.get(name) {
+            Some(id) => *id,
+            None => self.add_node(name, node_attr(name)),
+        }
+    }
+
+    /// Add a node, or replace the attribute of the node if the name is already known.
+    pub fn add_node(&mut self, name: &str, attr: N) -> NodeId {
+        if let Some(id) = self.ids.get(name) {
+            self.nodes[*id] = attr;
+            return *id;
+        }
+        let id = self.nodes.len();
+        self.names.push(name.to_string());
+        self.ids.insert(name.to_string(), id);
+        self.nodes.push(attr);
+        self.edges.push(Vec::new());
+        id
+    }
+
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, attr: E)
+    where
+        E: Clone,
+    {
+        if self.direction == Direction::Undirected && from != to {
+            self.edges[to].push((from, attr.clone()));
+        }
+        self.edges[from].push((to, attr));
+    }
+
+    pub fn direction(&self) -> Direction {
+        self.direction
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn id(&self, name: &str) -> Option<NodeId> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: NodeId) -> &str {
+        &self.names[id]
+    }
+
+    pub fn node(&self, id: NodeId) -> &N {
+        &self.nodes[id]
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> &mut N {
+        &mut self.nodes[id]
+    }
+
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> {
+        0..self.nodes.len()
+    }
+
+    /// Outgoing edges of a node, as '(target, attribute)' pairs.
+    pub fn edges(&self, id: NodeId) -> &[(NodeId, E)] {
+        &self.edges[id]
+    }
+
+    pub fn neighbors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.edges[id].iter().map(|(n, _)| *n)
+    }
+
+    /// Iterate over all nodes reachable from 'start' in breadth-first order.
+    pub fn bfs(&self, start: NodeId) -> Bfs<'_, N, E> {
+        Bfs {
+            graph: self,
+            queue: VecDeque::from([start]),
+            seen: HashSet::from([start]),
+        }
+    }
+
+    /// Iterate over all nodes reachable from 'start' in depth-first pre-order.
+    pub fn dfs(&self, start: NodeId) -> Dfs<'_, N, E> {
+        Dfs {
+            graph: self,
+            stack: vec![start],
+            seen: HashSet::new(),
+        }
+    }
+
+    /// Export the graph in Graphviz DOT format.
+    pub fn to_dot(&self) -> String {
+        self.to_dot_with(|_| String::new(), |_| String::new())
+    }
+
+    /// Export the graph in Graphviz DOT format. 'node_attrs' and 'edge_attrs' return the contents
+    /// of the attribute list for a node / edge (e.g. 'shape=box'), or an empty string for none.
+    pub fn to_dot_with<FN, FE>(&self, node_attrs: FN, edge_attrs: FE) -> String
+    where
+        FN: Fn(&N) -> String,
+        FE: Fn(&E) -> String,
+    {
+        // a DOT string is '"..."' with '"' and '\' escaped, newlines become '\n' to keep one
+        // statement per line, anything else goes in as it is
+        fn quote(name: &str) -> String {
+            let mut quoted = String::with_capacity(name.len() + 2);
+            quoted.push('"');
+            for c in name.chars() {
+                match c {
+                    '"' | '\\' => {
+                        quoted.push('\\');
+                        quoted.push(c);
+                    }
+                    '\n' => quoted.push_str("\\n"),
+                    c => quoted.push(c),
+                }
+            }
+            quoted.push('"');
+            quoted
+        }
+
+        fn attr_list(attrs: String) -> String {
+            if attrs.is_empty() {
+                attrs
+            } else {
+                format!(" [{}]", attrs)
+            }
+        }
+
+        let (keyword, arrow) = match self.direction {
+            Direction::Directed => ("digraph", "->"),
+            Direction::Undirected => ("graph", "--"),
+        };
+        let mut dot = format!("{} {{\n", keyword);
+        for id in self.node_ids() {
+            let attrs = attr_list(node_attrs(&self.nodes[id]));
+            writeln!(dot, "    {}{};", quote(&self.names[id]), attrs).unwrap();
+        }
+        for from in self.node_ids() {
+            for (to, e) in self.edges[from].iter() {
+                // undirected edges are stored twice, only print them once
+                if self.direction == Direction::Undirected && *to < from {
+                    continue;
+                }
+                let attrs = attr_list(edge_attrs(e));
+                let (from, to) = (&self.names[from], &self.names[*to]);
+                writeln!(dot, "    {} {} {}{};", quote(from), arrow, quote(to), attrs).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+pub struct Bfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    queue: VecDeque<NodeId>,
+    seen: HashSet<NodeId>,
+}
+
+impl<'a, N, E> Iterator for Bfs<'a, N, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        let id = self.queue.pop_front()?;
+        for n in self.graph.neighbors(id) {
+            if self.seen.insert(n) {
+                self.queue.push_back(n);
+            }
+        }
+        Some(id)
+    }
+}
+
+pub struct Dfs<'a, N, E> {
+    graph: &'a Graph<N, E>,
+    stack: Vec<NodeId>,
+    seen: HashSet<NodeId>,
+}
+
+impl<'a, N, E> Iterator for Dfs<'a, N, E> {
+    type Item = NodeId;
+
+    fn next(&mut self) -> Option<NodeId> {
+        while let Some(id) = self.stack.pop() {
+            if !self.seen.insert(id) {
+                continue;
+            }
+            // push in reverse so the first neighbor is visited first
+            let unseen = self.graph.neighbors(id).filter(|n| !self.seen.contains(n));
+            self.stack
+                .extend(unseen.collect::<Vec<_>>().into_iter().rev());
+            return Some(id);
+        }
+        None
+    }
+}
+
+/// Rules for how often a path may pass through a node, used by 'Graph::paths'.
+pub trait VisitPolicy<N> {
+    /// Maximum number of times a path may contain the node ('None' for no limit). Paths never go
+    /// round a cycle of unlimited nodes: an unlimited node may only be visited again if a limited
+    /// node was visited since, otherwise two adjacent unlimited nodes would allow endless paths.
+    fn visit_limit(&self, id: NodeId, node: &N) -> Option<usize>;
+
+    /// Number of extra visits a path may make in total beyond the per-node limits.
+    fn extra_visits(&self) -> usize {
+        0
+    }
+
+    /// Whether the extra visit budget may be spent on the node.
+    fn allows_extra_visit(&self, _id: NodeId, _node: &N) -> bool {
+        true
+    }
+}
+
+/// Declarative 'VisitPolicy': a per-node limit computed from the node attribute, a global budget
+/// of extra visits and a set of nodes that can never be re-entered.
+///
+/// E.g. "small caves at most once, but a single small cave other than 'start' twice" is
+/// 'VisitRules::new(|c| c.is_small().then_some(1)).extra_visits(1).no_reentry(start)'.
+pub struct VisitRules<F> {
+    limit: F,
+    extra_visits: usize,
+    no_reentry: HashSet<NodeId>,
+}
+
+impl<F> VisitRules<F> {
+    pub fn new(limit: F) -> Self {
+        VisitRules {
+            limit,
+            extra_visits: 0,
+            no_reentry: HashSet::new(),
+        }
+    }
+
+    pub fn extra_visits(mut self, extra_visits: usize) -> Self {
+        self.extra_visits = extra_visits;
+        self
+    }
+
+    pub fn no_reentry(mut self, id: NodeId) -> Self {
+        self.no_reentry.insert(id);
+        self
+    }
+}
+
+impl<N, F> VisitPolicy<N> for VisitRules<F>
+where
+    F: Fn(&N) -> Option<usize>,
+{
+    fn visit_limit(&self, id: NodeId, node: &N) -> Option<usize> {
+        if self.no_reentry.contains(&id) {
+            Some(1)
+        } else {
+            (self.limit)(node)
+        }
+    }
+
+    fn extra_visits(&self) -> usize {
+        self.extra_visits
+    }
+
+    fn allows_extra_visit(&self, id: NodeId, _node: &N) -> bool {
+        !self.no_reentry.contains(&id)
+    }
+}
+
+/// Visit counts of the path currently being built.
+struct Visits {
+    counts: Vec<usize>,
+    extra_used: usize,
+}
+
+impl Visits {
+    /// Check whether the policy allows one more visit of 'id' after 'path'. Returns whether that
+    /// visit has to use the extra budget, or 'None' if it is not allowed at all.
+    fn check<N, E, P: VisitPolicy<N>>(
+        &self,
+        graph: &Graph<N, E>,
+        policy: &P,
+        path: &[NodeId],
+        id: NodeId,
+    ) -> Option<bool> {
+        let node = graph.node(id);
+        let limit = policy.visit_limit(id, node);
+        if limit.is_none() && self.counts[id] > 0 && Self::unlimited_cycle(graph, policy, path, id)
+        {
+            None
+        } else if limit.is_none_or(|limit| self.counts[id] < limit) {
+            Some(false)
+        } else if self.extra_used < policy.extra_visits() && policy.allows_extra_visit(id, node) {
+            Some(true)
+        } else {
+            None
+        }
+    }
+
+    /// Whether 'path' visited 'id' last with only unlimited nodes after it.
+    fn unlimited_cycle<N, E, P: VisitPolicy<N>>(
+        graph: &Graph<N, E>,
+        policy: &P,
+        path: &[NodeId],
+        id: NodeId,
+    ) -> bool {
+        for &p in path.iter().rev() {
+            if p == id {
+                return true;
+            }
+            if policy.visit_limit(p, graph.node(p)).is_some() {
+                return false;
+            }
+        }
+        false
+    }
+
+    fn enter(&mut self, id: NodeId, extra: bool) {
+        self.counts[id] += 1;
+        self.extra_used += extra as usize;
+    }
+
+    fn leave(&mut self, id: NodeId, extra: bool) {
+        self.counts[id] -= 1;
+        self.extra_used -= extra as usize;
+    }
+}
+
+/// Iterator over all paths from a start to an end node, see 'Graph::paths'.
+pub struct Paths<'a, N, E, P> {
+    graph: &'a Graph<N, E>,
+    policy: P,
+    end: NodeId,
+    path: Vec<NodeId>,
+    // for each node on 'path': index of the next edge to follow and whether it used an extra visit
+    frames: Vec<(usize, bool)>,
+    visits: Visits,
+}
+
+impl<'a, N, E, P: VisitPolicy<N>> Iterator for Paths<'a, N, E, P> {
+    type Item = Vec<NodeId>;
+
+    fn next(&mut self) -> Option<Vec<NodeId>> {
+        loop {
+            let cur = *self.path.last()?;
+            let (next_edge, extra) = self.frames.last_mut().unwrap();
+            let Some((n, _)) = self.graph.edges(cur).get(*next_edge) else {
+                let extra = *extra;
+                self.frames.pop();
+                self.path.pop();
+                self.visits.leave(cur, extra);
+                continue;
+            };
+            *next_edge += 1;
+            let Some(extra) = self.visits.check(self.graph, &self.policy, &self.path, *n) else {
+                continue;
+            };
+            if *n == self.end {
+                let mut path = self.path.clone();
+                path.push(*n);
+                return Some(path);
+            }
+            self.path.push(*n);
+            self.frames.push((0, extra));
+            self.visits.enter(*n, extra);
+        }
+    }
+}
+
+impl<N, E> Graph<N, E> {
+    /// Lazily enumerate all paths from 'start' to 'end' that are allowed by 'policy'. A path ends
+    /// as soon as it reaches 'end'. There are finitely many, even with unlimited nodes, see
+    /// 'VisitPolicy::visit_limit'.
+    pub fn paths<P: VisitPolicy<N>>(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        policy: P,
+    ) -> Paths<'_, N, E, P> {
+        let mut visits = Visits {
+            counts: vec![0; self.len()],
+            extra_used: 0,
+        };
+        visits.enter(start, false);
+        Paths {
+            graph: self,
+            policy,
+            end,
+            path: vec![start],
+            frames: vec![(0, false)],
+            visits,
+        }
+    }
+
+    /// Sample a single path from 'start' to 'end' allowed by 'policy' with a random walk that
+    /// picks uniformly among the allowed neighbors at every step. Returns 'None' if the walk runs
+    /// into a dead end. Note that this does not sample uniformly from all paths. The walk always
+    /// ends, for the same reason 'paths' does.
+    pub fn random_path<P, R>(
+        &self,
+        start: NodeId,
+        end: NodeId,
+        policy: &P,
+        rng: &mut R,
+    ) -> Option<Vec<NodeId>>
+    where
+        P: VisitPolicy<N>,
+        R: rand::Rng,
+    {
+        let mut visits = Visits {
+            counts: vec![0; self.len()],
+            extra_used: 0,
+        };
+        visits.enter(start, false);
+        let mut path = vec![start];
+        while *path.last().unwrap() != end {
+            let allowed = self
+                .neighbors(*path.last().unwrap())
+                .filter_map(|n| visits.check(self, policy, &path, n).map(|extra| (n, extra)))
+                .collect::<Vec<_>>();
+            if allowed.is_empty() {
+                return None;
+            }
+            let (n, extra) = allowed[rng.gen_range(0..allowed.len())];
+            visits.enter(n, extra);
+            path.push(n);
+        }
+        Some(path)
+    }
+}
+
+#[cfg(test)]
+fn example_graph(direction: Direction) -> Graph<bool, ()> {
+    let edges = crate::parser::parse_all(
+        crate::parser::edge_list,
+        "start-A
+        start-b
+        A-c
+        A-b
+        b-d
+        A-end
+        b-end",
+    )
+    .unwrap();
+    Graph::from_edges(direction, edges, |name| {
+        name.chars().all(|c| c.is_ascii_lowercase())
+    })
+}
+
+#[test]
+fn test_graph() {
+    let g = example_graph(Direction::Undirected);
+    assert_eq!(g.len(), 6);
+    let a = g.id("A").unwrap();
+    assert_eq!(g.name(a), "A");
+    assert!(!*g.node(a));
+    assert!(*g.node(g.id("c").unwrap()));
+    let mut neighbors = g.neighbors(a).map(|n| g.name(n)).collect::<Vec<_>>();
+    neighbors.sort_unstable();
+    assert_eq!(neighbors, vec!["b", "c", "end", "start"]);
+    assert_eq!(g.id("x"), None);
+
+    let start = g.id("start").unwrap();
+    let bfs = g.bfs(start).map(|n| g.name(n)).collect::<Vec<_>>();
+    assert_eq!(bfs, vec!["start", "A", "b", "c", "end", "d"]);
+    let dfs = g.dfs(start).map(|n| g.name(n)).collect::<Vec<_>>();
+    assert_eq!(dfs, vec!["start", "A", "c", "b", "d", "end"]);
+
+    let g = example_graph(Direction::Directed);
+    let b = g.id("b").unwrap();
+    assert_eq!(
+        g.dfs(b).map(|n| g.name(n)).collect::<Vec<_>>(),
+        ["b", "d", "end"]
+    );
+}
+
+#[test]
+fn test_graph_dot() {
+    let g = example_graph(Direction::Undirected);
+    let dot = g.to_dot_with(
+        |small| {
+            if *small {
+                String::new()
+            } else {
+                "shape=box".to_string()
+            }
+        },
+        |_| String::new(),
+    );
+    assert!(dot.starts_with("graph {\n"));
+    assert!(dot.contains("    \"A\" [shape=box];\n"));
+    assert!(dot.contains("    \"b\";\n"));
+    assert!(dot.contains("    \"start\" -- \"A\";\n"));
+    assert_eq!(dot.matches("--").count(), 7);
+
+    let dot = example_graph(Direction::Directed).to_dot();
+    assert!(dot.starts_with("digraph {\n"));
+    assert!(dot.contains("    \"b\" -> \"end\";\n"));
+    assert_eq!(dot.matches("->").count(), 7);
+
+    // only '"', '\' and newlines get escaped, not the way Rust would do it
+    let mut g: Graph<(), ()> = Graph::new(Direction::Directed);
+    let quoted = g.add_node("say \"hi\"", ());
+    let other = g.add_node("it's a\\b\u{e9}", ());
+    g.add_edge(quoted, other, ());
+    assert_eq!(
+        g.to_dot(),
+        "digraph {\n    \"say \\\"hi\\\"\";\n    \"it's a\\\\b\u{e9}\";\n    \"say \\\"hi\\\"\" -> \"it's a\\\\b\u{e9}\";\n}\n"
+    );
+    let mut g: Graph<(), ()> = Graph::new(Direction::Undirected);
+    g.add_node("two\nlines\\", ());
+    assert_eq!(g.to_dot(), "graph {\n    \"two\\nlines\\\\\";\n}\n");
+}
+
+#[test]
+fn test_paths() {
+    use rand::SeedableRng;
+
+    let g = example_graph(Direction::Undirected);
+    let start = g.id("start").unwrap();
+    let end = g.id("end").unwrap();
+
+    let once = VisitRules::new(|small: &bool| small.then_some(1));
+    let paths = g.paths(start, end, once).collect::<Vec<_>>();
+    assert_eq!(paths.len(), 10);
+    assert!(paths
+        .iter()
+        .all(|p| p.first() == Some(&start) && p.last() == Some(&end)));
+    let named = paths
+        .iter()
+        .map(|p| p.iter().map(|n| g.name(*n)).collect::<Vec<_>>().join(","))
+        .collect::<HashSet<_>>();
+    assert_eq!(named.len(), 10);
+    assert!(named.contains("start,A,b,A,c,A,end"));
+    assert!(named.contains("start,b,end"));
+
+    let twice = || {
+        VisitRules::new(|small: &bool| small.then_some(1))
+            .extra_visits(1)
+            .no_reentry(start)
+    };
+    assert_eq!(g.paths(start, end, twice()).count(), 36);
+    assert_eq!(
+        g.paths(start, end, VisitRules::new(|_: &bool| Some(1)))
+            .count(),
+        4
+    );
+
+    let mut rng = rand::rngs::StdRng::seed_from_u64(12);
+    let policy = twice();
+    for _ in 0..20 {
+        if let Some(p) = g.random_path(start, end, &policy, &mut rng) {
+            assert!(g.paths(start, end, twice()).any(|q| q == p));
+        }
+    }
+}
+
+#[test]
+fn test_unlimited_cycle() {
+    use rand::SeedableRng;
+
+    // A and B are unlimited and next to each other, c is the only limited node between them
+    let edges = crate::parser::parse_all(
+        crate::parser::edge_list,
+        "start-A
+        A-B
+        B-c
+        c-A
+        B-end",
+    )
+    .unwrap();
+    let g: Graph<bool, ()> = Graph::from_edges(Direction::Undirected, edges, |name| {
+        name.chars().all(|c| c.is_ascii_lowercase())
+    });
+    let start = g.id("start").unwrap();
+    let end = g.id("end").unwrap();
+    let policy = || VisitRules::new(|small: &bool| small.then_some(1));
+
+    let mut named = g
+        .paths(start, end, policy())
+        .map(|p| p.iter().map(|n| g.name(*n)).collect::<Vec<_>>().join(","))
+        .collect::<Vec<_>>();
+    named.sort_unstable();
+    assert_eq!(
+        named,
+        [
+            "start,A,B,c,A,B,end",
+            "start,A,B,c,B,end",
+            "start,A,B,end",
+            "start,A,c,A,B,end",
+            "start,A,c,B,end",
+        ]
+    );
+
+    // with nothing but 'start' limited every walk still ends, at 'end' or in a dead end
+    let mut rng = rand::rngs::StdRng::seed_from_u64(31);
+    let unlimited = VisitRules::new(|_: &bool| None).no_reentry(start);
+    for _ in 0..20 {
+        let _ = g.random_path(start, end, &unlimited, &mut rng);
+    }
+    assert!(g.paths(start, end, unlimited).count() > 0);
+}
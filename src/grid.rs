@@ -0,0 +1,270 @@
+use std::{collections::HashMap, hash::Hash};
+
+use crate::{search::dijkstra, Vec2};
+
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum Dir2 {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl Dir2 {
+    pub const ALL: [Dir2; 4] = [Dir2::Up, Dir2::Down, Dir2::Left, Dir2::Right];
+
+    pub fn step(&self, pos: Vec2) -> Vec2 {
+        match self {
+            Dir2::Up => Vec2::new(pos.x, pos.y - 1),
+            Dir2::Down => Vec2::new(pos.x, pos.y + 1),
+            Dir2::Left => Vec2::new(pos.x - 1, pos.y),
+            Dir2::Right => Vec2::new(pos.x + 1, pos.y),
+        }
+    }
+
+    pub fn reverse(&self) -> Dir2 {
+        match self {
+            Dir2::Up => Dir2::Down,
+            Dir2::Down => Dir2::Up,
+            Dir2::Left => Dir2::Right,
+            Dir2::Right => Dir2::Left,
+        }
+    }
+}
+
+/// Describes how a walker moves through a grid of costs (as returned by 'read_i64_field').
+///
+/// The search state is up to the model, so it can carry e.g. the current heading or the length of
+/// the current straight run in addition to the position.
+pub trait MoveModel {
+    type State: Clone + Eq + Hash;
+
+    /// State of a walker standing on 'pos' before the first move.
+    fn start(&self, pos: Vec2) -> Self::State;
+
+    fn pos(&self, state: &Self::State) -> Vec2;
+
+    /// Possible moves from 'state', with the cost of each move.
+    fn moves(&self, state: &Self::State, field: &HashMap<Vec2, i64>) -> Vec<(Self::State, i64)>;
+
+    /// Whether a route may end in 'state' once it has reached the goal position.
+    fn can_stop(&self, _state: &Self::State) -> bool {
+        true
+    }
+}
+
+/// Find the cheapest route from 'start' to 'end' under the rules of 'model'.
+///
+/// Returns the total cost and the visited positions, including 'start' and 'end'.
+pub fn grid_path<M: MoveModel>(
+    field: &HashMap<Vec2, i64>,
+    start: Vec2,
+    end: Vec2,
+    model: &M,
+) -> Option<(i64, Vec<Vec2>)> {
+    let (states, cost) = dijkstra(
+        &model.start(start),
+        |s| model.moves(s, field),
+        |s| model.pos(s) == end && model.can_stop(s),
+    )?;
+    Some((cost, states.iter().map(|s| model.pos(s)).collect()))
+}
+
+/// Plain 4-neighbor moves, each costing the value of the entered cell.
+pub struct PlainMoves;
+
+impl MoveModel for PlainMoves {
+    type State = Vec2;
+
+    fn start(&self, pos: Vec2) -> Vec2 {
+        pos
+    }
+
+    fn pos(&self, state: &Vec2) -> Vec2 {
+        *state
+    }
+
+    fn moves(&self, state: &Vec2, field: &HashMap<Vec2, i64>) -> Vec<(Vec2, i64)> {
+        state
+            .ortho_neighbors()
+            .iter()
+            .filter_map(|n| field.get(n).map(|cost| (*n, *cost)))
+            .collect()
+    }
+}
+
+/// 4-neighbor moves costing the value of the entered cell, plus 'penalty' for every 90 degree
+/// change of heading (a reversal counts as two turns). The first move is free to pick its heading.
+pub struct TurnPenalty {
+    pub penalty: i64,
+}
+
+impl MoveModel for TurnPenalty {
+    type State = (Vec2, Option<Dir2>);
+
+    fn start(&self, pos: Vec2) -> Self::State {
+        (pos, None)
+    }
+
+    fn pos(&self, state: &Self::State) -> Vec2 {
+        state.0
+    }
+
+    fn moves(
+        &self,
+        (pos, heading): &Self::State,
+        field: &HashMap<Vec2, i64>,
+    ) -> Vec<(Self::State, i64)> {
+        Dir2::ALL
+            .iter()
+            .filter_map(|dir| {
+                let n = dir.step(*pos);
+                let turns = match heading {
+                    Some(h) if h == dir => 0,
+                    Some(h) if h.reverse() == *dir => 2,
+                    Some(_) => 1,
+                    None => 0,
+                };
+                field
+                    .get(&n)
+                    .map(|cost| ((n, Some(*dir)), cost + turns * self.penalty))
+            })
+            .collect()
+    }
+}
+
+/// 4-neighbor moves costing the value of the entered cell, where the walker has to go at least
+/// 'min_run' and at most 'max_run' cells in a straight line before it may turn (and before it may
+/// stop). Reversing is not allowed.
+pub struct RunLimited {
+    pub min_run: usize,
+    pub max_run: usize,
+}
+
+impl MoveModel for RunLimited {
+    /// position, heading and number of cells moved in that heading so far
+    type State = (Vec2, Option<Dir2>, usize);
+
+    fn start(&self, pos: Vec2) -> Self::State {
+        (pos, None, 0)
+    }
+
+    fn pos(&self, state: &Self::State) -> Vec2 {
+        state.0
+    }
+
+    fn moves(
+        &self,
+        (pos, heading, run): &Self::State,
+        field: &HashMap<Vec2, i64>,
+    ) -> Vec<(Self::State, i64)> {
+        Dir2::ALL
+            .iter()
+            .filter_map(|dir| {
+                let run = match heading {
+                    None => 1,
+                    Some(h) if h == dir && *run < self.max_run => run + 1,
+                    Some(h) if h != dir && h.reverse() != *dir && *run >= self.min_run => 1,
+                    _ => return None,
+                };
+                let n = dir.step(*pos);
+                field.get(&n).map(|cost| ((n, Some(*dir), run), *cost))
+            })
+            .collect()
+    }
+
+    fn can_stop(&self, (_, _, run): &Self::State) -> bool {
+        *run >= self.min_run
+    }
+}
+
+#[cfg(test)]
+const CRUCIBLE_EXAMPLE: &str = "2413432311323
+    3215453535623
+    3255245654254
+    3446585845452
+    4546657867536
+    1438598798454
+    4457876987766
+    3637877979653
+    4654967986887
+    4564679986453
+    1224686865563
+    2546548887735
+    4322674655533";
+
+#[test]
+fn test_plain_moves() {
+    let field = crate::read_i64_field(
+        "1163751742
+        1381373672
+        2136511328
+        3694931569
+        7463417111
+        1319128137
+        1359912421
+        3125421639
+        1293138521
+        2311944581",
+    );
+    let (cost, route) = grid_path(&field, Vec2::new(0, 0), Vec2::new(9, 9), &PlainMoves).unwrap();
+    assert_eq!(cost, 40);
+    assert_eq!(route.first(), Some(&Vec2::new(0, 0)));
+    assert_eq!(route.last(), Some(&Vec2::new(9, 9)));
+    assert_eq!(route.iter().skip(1).map(|p| field[p]).sum::<i64>(), 40);
+    assert_eq!(
+        grid_path(&field, Vec2::new(0, 0), Vec2::new(10, 10), &PlainMoves),
+        None
+    );
+}
+
+#[test]
+fn test_turn_penalty() {
+    let field = crate::read_i64_field(
+        "111
+        111
+        111",
+    );
+    let (cost, route) = grid_path(
+        &field,
+        Vec2::new(0, 0),
+        Vec2::new(2, 2),
+        &TurnPenalty { penalty: 10 },
+    )
+    .unwrap();
+    assert_eq!(cost, 14);
+    // a single turn means the route goes along two edges of the grid
+    assert!(route.contains(&Vec2::new(2, 0)) || route.contains(&Vec2::new(0, 2)));
+}
+
+#[test]
+fn test_run_limited() {
+    let field = crate::read_i64_field(CRUCIBLE_EXAMPLE);
+    let (start, end) = (Vec2::new(0, 0), Vec2::new(12, 12));
+    let crucible = RunLimited {
+        min_run: 0,
+        max_run: 3,
+    };
+    let (cost, route) = grid_path(&field, start, end, &crucible).unwrap();
+    assert_eq!(cost, 102);
+    assert_eq!(route.iter().skip(1).map(|p| field[p]).sum::<i64>(), 102);
+
+    let ultra = RunLimited {
+        min_run: 4,
+        max_run: 10,
+    };
+    assert_eq!(
+        grid_path(&field, start, end, &ultra).map(|(c, _)| c),
+        Some(94)
+    );
+
+    let field = crate::read_i64_field(
+        "111111111111
+        999999999991
+        999999999991
+        999999999991
+        999999999991",
+    );
+    let res = grid_path(&field, Vec2::new(0, 0), Vec2::new(11, 4), &ultra);
+    assert_eq!(res.map(|(c, _)| c), Some(71));
+}
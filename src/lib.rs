@@ -1,17 +1,36 @@
 use std::{
     collections::{HashMap, HashSet, VecDeque},
-    ops::{Add, Deref, RangeInclusive, Sub},
+    fmt,
+    iter::Sum,
+    ops::{Add, RangeInclusive, Sub},
 };
 
 use itertools::Itertools;
 
+pub mod boxn;
+pub mod cube_index;
+pub mod cycle;
+pub mod generators;
+pub mod graph;
+pub mod grid;
+pub mod lint;
 pub mod parser;
+pub mod range_set;
+pub mod search;
+pub mod signed_cubes;
+pub mod snailfish;
 
 #[derive(Debug, Clone, Copy, Default, Eq, PartialEq, Hash)]
 pub struct Vec2 {
     pub x: i64,
     pub y: i64,
 }
+impl fmt::Display for Vec2 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.x, self.y)
+    }
+}
+
 impl Vec2 {
     pub fn new(x: i64, y: i64) -> Vec2 {
         Vec2 { x, y }
@@ -155,6 +174,12 @@ pub struct Vec3 {
     pub z: i64,
 }
 
+impl fmt::Display for Vec3 {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{},{}", self.x, self.y, self.z)
+    }
+}
+
 impl Vec3 {
     pub fn new(x: i64, y: i64, z: i64) -> Self {
         Vec3 { x, y, z }
@@ -216,23 +241,47 @@ fn test_vec3() {
     );
 }
 
-#[derive(Debug)]
+/// Equality only looks at 'numbers', not at which of them have been marked, so a board compares
+/// equal to the one parsed back from its 'Display' form even after 'apply'.
+#[derive(Debug, Clone)]
 pub struct BingoBoard {
+    pub numbers: Vec<Vec<i64>>,
     pub column_count: [usize; 5],
     pub row_count: [usize; 5],
     pub field_map: HashMap<i64, (usize, usize)>,
 }
 
+impl PartialEq for BingoBoard {
+    fn eq(&self, other: &Self) -> bool {
+        self.numbers == other.numbers
+    }
+}
+
+impl Eq for BingoBoard {}
+
+impl fmt::Display for BingoBoard {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, line) in self.numbers.iter().enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{}", line.iter().map(|n| format!("{:>2}", n)).join(" "))?;
+        }
+        Ok(())
+    }
+}
+
 impl BingoBoard {
     pub fn new(numbers: Vec<Vec<i64>>) -> Self {
         let mut field_map = HashMap::new();
-        for row in 0..5 {
-            for column in 0..5 {
-                field_map.insert(numbers[row][column], (column, row));
+        for (row, line) in numbers.iter().enumerate().take(5) {
+            for (column, number) in line.iter().enumerate().take(5) {
+                field_map.insert(*number, (column, row));
             }
         }
 
         Self {
+            numbers,
             field_map,
             row_count: [0; 5],
             column_count: [0; 5],
@@ -262,23 +311,19 @@ pub fn lowercase_char_to_index(c: char) -> usize {
     (c as u8 - b'a') as usize
 }
 
-fn char_to_int(c: char) -> i64 {
-    assert!(c.is_digit(10));
-    (c as u8 - b'0') as i64
-}
-
+/// Parse a field of single digits, one row per line. Panics on anything else.
 pub fn read_i64_field(s: &str) -> HashMap<Vec2, i64> {
-    s.trim()
-        .lines()
+    let rows = parser::parse_all(parser::digit_field, s).unwrap();
+    rows.into_iter()
         .enumerate()
-        .flat_map(|(y, line)| {
-            line.trim().chars().enumerate().map(move |(x, c)| {
+        .flat_map(|(y, row)| {
+            row.into_iter().enumerate().map(move |(x, n)| {
                 (
                     Vec2 {
                         x: x as i64,
                         y: y as i64,
                     },
-                    char_to_int(c),
+                    n,
                 )
             })
         })
@@ -291,7 +336,6 @@ pub fn read_i64_field(s: &str) -> HashMap<Vec2, i64> {
 /// - 'start' is the starting node.
 /// - 'successors' returns the list of successors for a given node.
 /// - 'success' checks whether the goal has been reached
-
 pub fn bfs_count_paths<S, FN, FS, IN>(start: S, mut successors: FN, mut success: FS) -> usize
 where
     S: Clone,
@@ -307,12 +351,92 @@ where
             count += 1;
             continue;
         }
-        queue.extend(successors(&s).into_iter());
+        queue.extend(successors(&s));
     }
 
     count
 }
 
+/// Error returned by 'count_paths_memo' if the state space contains a cycle. Holds one of the states
+/// on the cycle.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CycleDetected<S>(pub S);
+
+/// Compute the same number as 'bfs_count_paths', but without enumerating every path.
+///
+/// The number of completions is cached per state, so the cost grows with the number of distinct
+/// states instead of the number of paths. This requires the state space to be acyclic (which is
+/// the case whenever every move makes progress, e.g. by adding to a visited set). If a cycle is
+/// found, an error is returned instead, since the number of paths would be infinite.
+pub fn count_paths_memo<S, FN, FS, IN>(
+    start: S,
+    mut successors: FN,
+    mut success: FS,
+) -> Result<usize, CycleDetected<S>>
+where
+    S: Clone + Eq + std::hash::Hash,
+    FN: FnMut(&S) -> IN,
+    IN: IntoIterator<Item = S>,
+    FS: FnMut(&S) -> bool,
+{
+    if success(&start) {
+        return Ok(1);
+    }
+    let mut memo = HashMap::<S, usize>::new();
+    let mut on_stack = HashSet::new();
+    // depth first: each frame holds a state, its not yet counted successors and the count so far
+    let mut stack = vec![(
+        start.clone(),
+        successors(&start).into_iter().collect_vec(),
+        0,
+    )];
+    on_stack.insert(start);
+
+    loop {
+        let (_, pending, count) = stack.last_mut().unwrap();
+        if let Some(next) = pending.pop() {
+            if success(&next) {
+                *count += 1;
+            } else if let Some(n) = memo.get(&next) {
+                *count += n;
+            } else if on_stack.contains(&next) {
+                return Err(CycleDetected(next));
+            } else {
+                let pending = successors(&next).into_iter().collect_vec();
+                on_stack.insert(next.clone());
+                stack.push((next, pending, 0));
+            }
+            continue;
+        }
+        let (state, _, count) = stack.pop().unwrap();
+        match stack.last_mut() {
+            Some((_, _, parent_count)) => *parent_count += count,
+            None => return Ok(count),
+        }
+        on_stack.remove(&state);
+        memo.insert(state, count);
+    }
+}
+
+#[test]
+fn test_count_paths_memo() {
+    // paths through a 6x6 grid moving only right or down
+    let successors = |(x, y): &(i64, i64)| {
+        [(x + 1, *y), (*x, y + 1)]
+            .into_iter()
+            .filter(|(x, y)| *x < 6 && *y < 6)
+            .collect::<Vec<_>>()
+    };
+    let success = |p: &(i64, i64)| *p == (5, 5);
+    assert_eq!(count_paths_memo((0, 0), successors, success), Ok(252));
+    assert_eq!(bfs_count_paths((0, 0), successors, success), 252);
+    assert_eq!(count_paths_memo((5, 5), successors, success), Ok(1));
+    assert_eq!(count_paths_memo((5, 0), |_| vec![], success), Ok(0));
+
+    let cyclic = |n: &i64| vec![(n + 1) % 4, 10];
+    assert!(count_paths_memo(0, cyclic, |n| *n == 10).is_err());
+}
+
 pub fn count_occurrences<T: Eq + Clone + std::hash::Hash, IN: IntoIterator<Item = T>>(
     i: IN,
 ) -> HashMap<T, i64> {
@@ -370,51 +494,19 @@ pub fn dump_bool_field(field: &HashSet<Vec2>) {
     }
 }
 
-// pub enum SnDir {
-//     None,
-//     Left,
-//     Right,
-// }
-
-// pub enum SnLink {
-//     Number(i64),
-//     Node(usize),
-// }
-
-// pub struct SnNode {
-//     from_dir: SnDir,
-//     parent: usize,
-//     id: usize,
-//     left: SnLink,
-//     right: SnLink,
-// }
-
-// #[derive(Default)]
-// pub struct SnNodes {
-//     next: usize,
-//     nodes: HashMap<usize, SnNode>,
-// }
-
-// impl SnNodes {
-//     pub fn put(&mut self, mut node: SnNode) {
-//         node.id = self.next;
-//         self.next += 1;
-//         self.nodes.insert(node.id, node);
-//     }
-
-//     pub fn get(&self, id: usize) -> &SnNode {
-//         self.nodes.get(&id).unwrap()
-//     }
-//     pub fn get_mut(&mut self, id: usize) -> &SnNode {
-//         self.nodes.get_mut(&id).unwrap()
-//     }
-// }
-
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum SfNumber {
     Number(i64),
     Pair(Box<SfNumber>, Box<SfNumber>),
-    Exploded,
+}
+
+impl fmt::Display for SfNumber {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SfNumber::Number(n) => write!(f, "{}", n),
+            SfNumber::Pair(l, r) => write!(f, "[{},{}]", l, r),
+        }
+    }
 }
 
 impl SfNumber {
@@ -425,122 +517,138 @@ impl SfNumber {
                 l.traverse_left_to_right();
                 r.traverse_left_to_right();
             }
-            _ => panic!("bad node type"),
         }
     }
 
     pub fn traverse_left_to_right_vec(&mut self, level: usize) -> Vec<(&mut SfNumber, usize)> {
         match self {
-            SfNumber::Number(v) => vec![(self, level)],
+            SfNumber::Number(_) => vec![(self, level)],
             SfNumber::Pair(l, r) => {
                 let mut vl = l.traverse_left_to_right_vec(level + 1);
                 let mut vr = r.traverse_left_to_right_vec(level + 1);
                 vl.append(&mut vr);
                 vl
             }
-            _ => panic!("bad node"),
-        }
-    }
-    pub fn is_exploded(&self) -> bool {
-        matches!(self, SfNumber::Exploded)
-    }
-    pub fn prune_exploded(&mut self) {
-        match self {
-            SfNumber::Pair(a, b) if a.is_exploded() && b.is_exploded() => {
-                *self = SfNumber::Number(0)
-            }
-            SfNumber::Pair(a, b) => {
-                a.prune_exploded();
-                b.prune_exploded();
-            }
-            _ => {}
         }
     }
 
     pub fn split(&mut self) -> bool {
+        self.split_by(&snailfish::SnailfishRules::default())
+    }
+
+    /// Split the leftmost regular number above 'rules.split_above', if there is one.
+    pub fn split_by(&mut self, rules: &snailfish::SnailfishRules) -> bool {
         match self {
-            SfNumber::Number(n) if *n > 9 => {
+            SfNumber::Number(n) if *n > rules.split_above => {
                 *self = SfNumber::Pair(
                     Box::new(SfNumber::Number(*n / 2)),
                     Box::new(SfNumber::Number(*n / 2 + *n % 2)),
                 );
                 true
             }
-            SfNumber::Pair(a, b) => a.split() || b.split(),
+            SfNumber::Pair(a, b) => a.split_by(rules) || b.split_by(rules),
             _ => false,
         }
     }
 
+    /// A lone regular number is left as it is, only pairs get reduced.
     pub fn reduce(&mut self) {
-        loop {
-            {
-                let mut v = self.traverse_left_to_right_vec(0);
-                println!("{:?}", v);
-
-                if let Some((explode_pos, _)) = v
-                    .windows(2)
-                    .find_position(|x| x[0].1 == x[1].1 && x[0].1 >= 5)
-                {
-                    // println!("explode: {:?}", explode_pos);
-                    // *v[explode_pos].0 = SfNumber::Exploded;
-                    // *v[explode_pos + 1].0 = SfNumber::Exploded;
-
-                    if explode_pos > 0 {
-                        let n = match (&v[explode_pos - 1].0, &v[explode_pos].0) {
-                            (SfNumber::Number(a), SfNumber::Number(b)) => a + b,
-                            _ => panic!("bad nodes"),
-                        };
-                        *v[explode_pos - 1].0 = SfNumber::Number(n);
-                    }
-                    if explode_pos < v.len() - 2 {
-                        let n = match (&v[explode_pos + 1].0, &v[explode_pos + 2].0) {
-                            (SfNumber::Number(a), SfNumber::Number(b)) => a + b,
-                            _ => panic!("bad nodes"),
-                        };
-                        *v[explode_pos + 2].0 = SfNumber::Number(n);
-                    }
-                    *v[explode_pos].0 = SfNumber::Exploded;
-                    *v[explode_pos + 1].0 = SfNumber::Exploded;
-                    self.prune_exploded();
-                    continue;
-                } else if self.split() {
-                    // println!("split");
-                    continue;
-                }
+        self.reduce_by(&snailfish::SnailfishRules::default());
+    }
+
+    pub fn reduce_by(&mut self, rules: &snailfish::SnailfishRules) {
+        if let SfNumber::Pair(l, r) = self {
+            let mut nodes = snailfish::SnNodes::from_pair(l, r);
+            nodes.reduce_by(rules);
+            *self = nodes.to_sf();
+        }
+    }
+
+    /// Reduce and return the explodes and splits it took, see 'SnNodes::reduce_with_trace'.
+    pub fn reduce_with_trace(&mut self) -> Vec<snailfish::SnStep> {
+        self.reduce_with_trace_by(&snailfish::SnailfishRules::default())
+    }
+
+    pub fn reduce_with_trace_by(
+        &mut self,
+        rules: &snailfish::SnailfishRules,
+    ) -> Vec<snailfish::SnStep> {
+        match self {
+            SfNumber::Pair(l, r) => {
+                let mut nodes = snailfish::SnNodes::from_pair(l, r);
+                let steps = nodes.reduce_with_trace_by(rules);
+                *self = nodes.to_sf();
+                steps
             }
-            break;
+            SfNumber::Number(_) => Vec::new(),
         }
     }
+
     pub fn get_magnitude(&self) -> i64 {
+        self.get_magnitude_by(&snailfish::SnailfishRules::default())
+    }
+
+    pub fn get_magnitude_by(&self, rules: &snailfish::SnailfishRules) -> i64 {
         match self {
             SfNumber::Number(n) => *n,
-            SfNumber::Pair(a, b) => 3 * a.get_magnitude() + 2 * b.get_magnitude(),
-            _ => panic!("bad node"),
+            SfNumber::Pair(a, b) => {
+                rules.weights.0 * a.get_magnitude_by(rules)
+                    + rules.weights.1 * b.get_magnitude_by(rules)
+            }
         }
     }
 }
 
-#[derive(Debug, Clone, Eq, PartialEq, Hash)]
-pub struct Cube {
-    pub xrange: RangeInclusive<i64>,
-    pub yrange: RangeInclusive<i64>,
-    pub zrange: RangeInclusive<i64>,
+/// The reduced sum.
+impl Add for SfNumber {
+    type Output = SfNumber;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        let mut sum = SfNumber::Pair(Box::new(self), Box::new(rhs));
+        sum.reduce();
+        sum
+    }
 }
 
-impl Cube {
-    pub fn new(
-        xrange: RangeInclusive<i64>,
-        yrange: RangeInclusive<i64>,
-        zrange: RangeInclusive<i64>,
-    ) -> Self {
-        Cube {
-            xrange,
-            yrange,
-            zrange,
-        }
+/// Snailfish addition has no neutral element, so the sum of nothing is None.
+impl Sum<SfNumber> for Option<SfNumber> {
+    fn sum<I: Iterator<Item = SfNumber>>(iter: I) -> Self {
+        iter.reduce(|a, b| a + b)
     }
 }
 
+impl<'a> Sum<&'a SfNumber> for Option<SfNumber> {
+    fn sum<I: Iterator<Item = &'a SfNumber>>(iter: I) -> Self {
+        iter.cloned().sum()
+    }
+}
+
+#[test]
+fn test_sf_number_arithmetic() {
+    let n = |s: &str| s.parse::<SfNumber>().unwrap();
+    assert_eq!(
+        n("[[[[4,3],4],4],[7,[[8,4],9]]]") + n("[1,1]"),
+        n("[[[[0,7],4],[[7,8],[6,0]]],[8,1]]")
+    );
+
+    let sum: Option<SfNumber> = ["[1,1]", "[2,2]", "[3,3]", "[4,4]", "[5,5]", "[6,6]"]
+        .iter()
+        .map(|s| n(s))
+        .sum();
+    assert_eq!(sum.unwrap().to_string(), "[[[[5,0],[7,4]],[5,5]],[6,6]]");
+    assert_eq!(
+        std::iter::empty::<SfNumber>().sum::<Option<SfNumber>>(),
+        None
+    );
+
+    // a lone regular number is not a pair and is left alone
+    let mut x = n("12");
+    x.reduce();
+    assert_eq!(x.get_magnitude(), 12);
+}
+
+pub type Cube = boxn::BoxN<3>;
+
 #[derive(Clone, Copy, Eq, PartialEq, Hash, Debug)]
 pub enum Overlap {
     Equals,
@@ -551,7 +659,7 @@ pub enum Overlap {
     None,
 }
 
-fn overlap_type(a: RangeInclusive<i64>, b: RangeInclusive<i64>) -> Overlap {
+pub(crate) fn overlap_type(a: RangeInclusive<i64>, b: RangeInclusive<i64>) -> Overlap {
     let astart = a.start();
     let aend = a.end();
     let bstart = b.start();
@@ -603,171 +711,14 @@ fn test_overlap() {
     assert_eq!(overlap_type(-10..=-5, -4..=10), Overlap::None);
 }
 
-impl Cube {
-    pub fn overlaps(&self, other: &Cube) -> bool {
-        overlap_type(self.xrange.clone(), other.xrange.clone()) != Overlap::None
-            && overlap_type(self.yrange.clone(), other.yrange.clone()) != Overlap::None
-            && overlap_type(self.zrange.clone(), other.zrange.clone()) != Overlap::None
-    }
-
-    pub fn split_side(
-        s: RangeInclusive<i64>,
-        r: RangeInclusive<i64>,
-    ) -> (Vec<RangeInclusive<i64>>, Vec<RangeInclusive<i64>>) {
-        let sstart = *s.start();
-        let send = *s.end();
-        let rstart = *r.start();
-        let rend = *r.end();
-
-        let overlap_type = overlap_type(r, s.clone());
-
-        match overlap_type {
-            Overlap::Equals | Overlap::Contains => (vec![s], vec![]),
-            Overlap::Contained => {
-                // Contained:       . . . . . . .
-                //              . . . . . . . . . .
-                assert!(rstart <= rend);
-                assert!(sstart < rstart);
-                assert!(rend < send);
-                (
-                    vec![rstart..=rend],
-                    vec![sstart..=rstart - 1, rend + 1..=send],
-                )
-            }
-            Overlap::Start => {
-                //  Start:      . . . .
-                //                  . .
-                //                  . . . . . .
-                assert!(sstart <= rend);
-                assert!(rend < send);
-                (vec![sstart..=rend], vec![rend + 1..=send])
-            }
-            Overlap::End => {
-                // End:             . . . . . .
-                //                  . .
-                //              . . . .
-                assert!(rstart <= send);
-                assert!(sstart < rstart);
-                (vec![rstart..=send], vec![sstart..=rstart - 1])
-            }
-            Overlap::None => (vec![], vec![s]),
-        }
-    }
-
-    pub fn split(&self, rcube: &Cube) -> Option<(Vec<Cube>, Vec<Cube>)> {
-        let mut new_cubes_out = Vec::new();
-        let (in_range, out_range) = Cube::split_side(self.xrange.clone(), rcube.xrange.clone());
-
-        let new_cubes_in_x = in_range
-            .iter()
-            .cloned()
-            .map(|xrange| Cube {
-                xrange,
-                yrange: self.yrange.clone(),
-                zrange: self.zrange.clone(),
-            })
-            .collect::<Vec<_>>();
-        new_cubes_out.extend(out_range.iter().cloned().map(|xrange| Cube {
-            xrange,
-            yrange: self.yrange.clone(),
-            zrange: self.zrange.clone(),
-        }));
-
-        if new_cubes_in_x.is_empty() {
-            return None;
-        }
-
-        let mut new_cubes_in_y = Vec::new();
-        for cube in new_cubes_in_x {
-            let (in_range, out_range) = Cube::split_side(cube.yrange.clone(), rcube.yrange.clone());
-
-            new_cubes_in_y.extend(in_range.iter().cloned().map(|yrange| Cube {
-                xrange: cube.xrange.clone(),
-                yrange,
-                zrange: cube.zrange.clone(),
-            }));
-
-            new_cubes_out.extend(out_range.iter().cloned().map(|yrange| Cube {
-                xrange: cube.xrange.clone(),
-                yrange,
-                zrange: cube.zrange.clone(),
-            }));
-        }
-
-        if new_cubes_in_y.is_empty() {
-            return None;
-        }
-
-        let mut new_cubes_in_z = Vec::new();
-        for cube in new_cubes_in_y {
-            let (in_range, out_range) = Cube::split_side(cube.zrange.clone(), rcube.zrange.clone());
-
-            new_cubes_in_z.extend(in_range.iter().cloned().map(|zrange| Cube {
-                xrange: cube.xrange.clone(),
-                yrange: cube.yrange.clone(),
-                zrange,
-            }));
-
-            new_cubes_out.extend(out_range.iter().cloned().map(|zrange| Cube {
-                xrange: cube.xrange.clone(),
-                yrange: cube.yrange.clone(),
-                zrange,
-            }));
-        }
-
-        assert_eq!(
-            new_cubes_in_z.iter().fold(0, |a, c| a + c.volume())
-                + new_cubes_out.iter().fold(0, |a, c| a + c.volume()),
-            self.volume()
-        );
-
-        Some((new_cubes_in_z, new_cubes_out))
-    }
-
-    pub fn volume(&self) -> i64 {
-        let xstart = self.xrange.start();
-        let xend = self.xrange.end();
-
-        let ystart = self.yrange.start();
-        let yend = self.yrange.end();
-
-        let zstart = self.zrange.start();
-        let zend = self.zrange.end();
-
-        (xend - xstart + 1) * (yend - ystart + 1) * (zend - zstart + 1)
-    }
-}
-
 #[test]
 fn test_cube_split() {
-    let a = Cube {
-        xrange: 10..=12,
-        yrange: 10..=12,
-        zrange: 10..=12,
-    };
-    let b = Cube {
-        xrange: 11..=13,
-        yrange: 10..=12,
-        zrange: 10..=12,
-    };
+    let a = Cube::new(10..=12, 10..=12, 10..=12);
+    let b = Cube::new(11..=13, 10..=12, 10..=12);
 
     let (inside, outside) = a.split(&b).unwrap();
-    assert_eq!(
-        inside,
-        vec![Cube {
-            xrange: 11..=12,
-            yrange: 10..=12,
-            zrange: 10..=12,
-        }]
-    );
-    assert_eq!(
-        outside,
-        vec![Cube {
-            xrange: 10..=10,
-            yrange: 10..=12,
-            zrange: 10..=12,
-        }]
-    );
+    assert_eq!(inside, vec![Cube::new(11..=12, 10..=12, 10..=12)]);
+    assert_eq!(outside, vec![Cube::new(10..=10, 10..=12, 10..=12)]);
     println!(
         "{:?} {:?} {} {}",
         inside[0],
@@ -780,24 +731,12 @@ fn test_cube_split() {
 
 #[test]
 fn test_cube() {
-    let s = Cube {
-        xrange: 10..=12,
-        yrange: 10..=12,
-        zrange: 10..=12,
-    };
+    let s = Cube::new(10..=12, 10..=12, 10..=12);
     assert_eq!(s.volume(), 27);
 
-    let s = Cube {
-        xrange: -10..=10,
-        yrange: -15..=15,
-        zrange: -20..=20,
-    };
+    let s = Cube::new(-10..=10, -15..=15, -20..=20);
 
-    let r = Cube {
-        xrange: -2..=3,
-        yrange: -4..=5,
-        zrange: -6..=7,
-    };
+    let r = Cube::new(-2..=3, -4..=5, -6..=7);
 
     let (in_cubes, out_cubes) = s.split(&r).unwrap();
     assert_eq!(in_cubes.len(), 1);
@@ -816,63 +755,112 @@ fn test_cube() {
             .fold(0, |a, c| a + c.volume())
     );
 
-    assert_eq!(
-        Cube {
-            xrange: -5..=5,
-            yrange: -5..=5,
-            zrange: -5..=5
-        }
-        .volume(),
-        11 * 11 * 11
-    );
+    assert_eq!(Cube::new(-5..=5, -5..=5, -5..=5).volume(), 11 * 11 * 11);
 }
 
-#[derive(Default)]
+/// Disjoint cubes, indexed by where they are so that only the cubes near a new cube get split.
+/// Two sets are equal if they cover the same points, however they are cut into cubes.
+#[derive(Debug, Clone, Default)]
 pub struct CubeSet {
-    cubes: Vec<Cube>,
+    cubes: cube_index::CubeIndex,
 }
 
 impl CubeSet {
     pub fn add(&mut self, add_cube: Cube, on_off: bool) {
-        // println!("\nadd {:?} {:?}", add_cube, on_off);
-        let mut new_cubes = Vec::new();
-        for cube in self.cubes.drain(..) {
-            // println!("{:?}", cube);
-
+        if add_cube.is_empty() {
+            return;
+        }
+        for id in self.cubes.overlapping(&add_cube) {
+            let cube = self.cubes.remove(id).unwrap();
             if let Some((_in_cubes, out_cubes)) = cube.split(&add_cube) {
-                // println!("-> {:?} {:?}", _in_cubes, out_cubes);
-                new_cubes.extend(out_cubes);
-            } else {
-                // println!("no overlap");
-                new_cubes.push(cube);
+                for out in out_cubes {
+                    self.cubes.insert(out);
+                }
             }
         }
 
-        self.cubes = new_cubes;
         if on_off {
-            self.cubes.push(add_cube);
+            self.cubes.insert(add_cube);
         }
+    }
 
-        // let vol = self.cubes.iter().fold(0, |a, c| a + c.volume());
+    fn cubes(&self) -> impl Iterator<Item = &Cube> {
+        self.cubes.iter().map(|(_, c)| c)
     }
 
-    pub fn volume(&self) -> i64 {
-        self.cubes.iter().fold(0, |a, c| a + c.volume())
+    pub fn union(&self, other: &CubeSet) -> CubeSet {
+        let mut union = self.clone();
+        for cube in other.cubes() {
+            union.add(cube.clone(), true);
+        }
+        union
     }
-    pub fn volume_intersect(&self, add_cube: &Cube) -> i64 {
-        let mut new_cubes = Vec::new();
-        for cube in self.cubes.iter() {
-            // println!("{:?}", cube);
 
-            if let Some((in_cubes, _out_cubes)) = cube.split(add_cube) {
-                // println!("-> {:?} {:?}", _in_cubes, out_cubes);
-                new_cubes.extend(in_cubes);
+    pub fn difference(&self, other: &CubeSet) -> CubeSet {
+        let mut difference = self.clone();
+        for cube in other.cubes() {
+            difference.add(cube.clone(), false);
+        }
+        difference
+    }
+
+    pub fn intersection(&self, other: &CubeSet) -> CubeSet {
+        // the cubes of either set are disjoint, so their pairwise intersections are too
+        let mut intersection = CubeSet::default();
+        for cube in self.cubes() {
+            for id in other.cubes.overlapping(cube) {
+                if let Some(common) = other.cubes.get(id).and_then(|c| c.intersection(cube)) {
+                    intersection.cubes.insert(common);
+                }
             }
         }
-        new_cubes.iter().fold(0, |a, c| a + c.volume())
+        intersection
+    }
+
+    pub fn symmetric_difference(&self, other: &CubeSet) -> CubeSet {
+        let mut symmetric_difference = self.difference(other);
+        for cube in other.difference(self).cubes() {
+            symmetric_difference.cubes.insert(cube.clone());
+        }
+        symmetric_difference
+    }
+
+    pub fn contains(&self, pos: Vec3) -> bool {
+        !self
+            .cubes
+            .overlapping(&Cube::new(pos.x..=pos.x, pos.y..=pos.y, pos.z..=pos.z))
+            .is_empty()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cubes.is_empty()
+    }
+
+    /// The smallest cube holding all of the set, None if it is empty.
+    pub fn bounding_box(&self) -> Option<Cube> {
+        self.cubes().cloned().reduce(|a, b| a.hull(&b))
+    }
+
+    pub fn volume(&self) -> i64 {
+        self.cubes.iter().fold(0, |a, (_, c)| a + c.volume())
+    }
+    pub fn volume_intersect(&self, add_cube: &Cube) -> i64 {
+        self.cubes
+            .overlapping(add_cube)
+            .into_iter()
+            .filter_map(|id| self.cubes.get(id)?.intersection(add_cube))
+            .fold(0, |a, c| a + c.volume())
     }
 }
 
+impl PartialEq for CubeSet {
+    fn eq(&self, other: &Self) -> bool {
+        self.volume() == other.volume() && self.symmetric_difference(other).is_empty()
+    }
+}
+
+impl Eq for CubeSet {}
+
 #[test]
 pub fn test_cube_set() {
     let mut cube_set = CubeSet::default();
@@ -882,6 +870,50 @@ pub fn test_cube_set() {
     cube_set.add(Cube::new(0..=0, 0..=0, 0..=0), false);
     assert_eq!(cube_set.volume(), 5);
 }
+#[test]
+fn test_cube_set_algebra() {
+    let set = |cubes: &[Cube]| {
+        let mut set = CubeSet::default();
+        for cube in cubes {
+            set.add(cube.clone(), true);
+        }
+        set
+    };
+    let a = set(&[Cube::new(0..=9, 0..=9, 0..=9)]);
+    let b = set(&[Cube::new(5..=14, 5..=14, 5..=14)]);
+
+    assert_eq!(a.union(&b).volume(), 2000 - 125);
+    assert_eq!(a.intersection(&b), set(&[Cube::new(5..=9, 5..=9, 5..=9)]));
+    assert_eq!(a.difference(&b).volume(), 875);
+    assert_eq!(a.symmetric_difference(&b).volume(), 1750);
+    assert_eq!(
+        a.symmetric_difference(&b),
+        a.union(&b).difference(&a.intersection(&b))
+    );
+    assert!(a.difference(&a).is_empty());
+    assert!(CubeSet::default().bounding_box().is_none());
+    assert_eq!(
+        a.union(&b).bounding_box(),
+        Some(Cube::new(0..=14, 0..=14, 0..=14))
+    );
+
+    assert!(a.contains(Vec3::new(9, 0, 9)));
+    assert!(!a.contains(Vec3::new(10, 0, 9)));
+    assert!(!a.difference(&b).contains(Vec3::new(7, 7, 7)));
+
+    // the same points cut into different cubes
+    let halves = set(&[
+        Cube::new(0..=4, 0..=9, 0..=9),
+        Cube::new(5..=9, 0..=9, 0..=9),
+    ]);
+    assert_eq!(halves, a);
+    assert_ne!(halves, b);
+    let (start, end) = (3, 2);
+    let mut with_empty = a.clone();
+    with_empty.add(Cube::new(start..=end, 0..=0, 0..=0), true);
+    assert_eq!(with_empty, a);
+}
+
 #[test]
 pub fn test_cube_set_vol() {
     let mut cube_set = CubeSet::default();
@@ -0,0 +1,459 @@
+//! Structural checks for puzzle inputs, so a bad input can be told apart from a buggy solver.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+};
+
+use itertools::Itertools;
+use nom::{character::complete::space1, multi::separated_list1, sequence::separated_pair};
+
+use crate::{
+    parser::{
+        blank_lines, blocks_separated_by_blank_lines, coord2d, edge, fold_instruction,
+        line_segment, lines_of, located, parse_all, pixel_row, reboot_step, scanner,
+        seven_segment_sample, signed_decimal, signed_decimal_comma_separated_list,
+        snailfish_number, submarine_command, FoldInstruction, ParseError, Span, SubmarineCommand,
+    },
+    SfNumber,
+};
+
+/// Something wrong with an input, lines and columns start at 1.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Problem {
+    pub line: u32,
+    pub column: usize,
+    pub message: String,
+}
+
+impl Problem {
+    fn at(pos: &Span<'_>, message: impl Into<String>) -> Problem {
+        Problem {
+            line: pos.location_line(),
+            column: pos.get_utf8_column(),
+            message: message.into(),
+        }
+    }
+}
+
+impl From<ParseError> for Problem {
+    fn from(e: ParseError) -> Problem {
+        Problem {
+            line: e.line,
+            column: e.column,
+            message: format!("can't parse input ({})", e.kind.description()),
+        }
+    }
+}
+
+impl fmt::Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.line, self.column, self.message)
+    }
+}
+
+/// Parse 'input' as the input of 'day' and check it. Returns None for days without checks, an
+/// input that doesn't parse gives a single problem at the place where parsing stopped.
+pub fn lint(day: u32, input: &str) -> Option<Vec<Problem>> {
+    let res = match day {
+        2 => lint_submarine_commands(input),
+        4 => lint_bingo(input),
+        5 => lint_line_segments(input),
+        8 => lint_seven_segment(input),
+        12 => lint_caves(input),
+        13 => lint_folds(input),
+        18 => lint_snailfish(input),
+        19 => lint_scanners(input),
+        20 => lint_image(input),
+        22 => lint_reboot_steps(input),
+        _ => return None,
+    };
+    Some(res.unwrap_or_else(|e| vec![e.into()]))
+}
+
+fn lint_submarine_commands(input: &str) -> Result<Vec<Problem>, ParseError> {
+    let commands = parse_all(lines_of(located(submarine_command)), input)?;
+    Ok(commands
+        .iter()
+        .filter_map(|(pos, command)| match command {
+            SubmarineCommand::Up(i) | SubmarineCommand::Down(i) | SubmarineCommand::Forward(i)
+                if *i < 0 =>
+            {
+                Some(Problem::at(pos, format!("negative distance {}", i)))
+            }
+            _ => None,
+        })
+        .collect())
+}
+
+fn lint_bingo(input: &str) -> Result<Vec<Problem>, ParseError> {
+    let ((numbers_pos, numbers), boards) = parse_all(
+        separated_pair(
+            located(signed_decimal_comma_separated_list),
+            blank_lines,
+            blocks_separated_by_blank_lines(lines_of(located(separated_list1(
+                space1,
+                signed_decimal,
+            )))),
+        ),
+        input,
+    )?;
+
+    let mut problems = Vec::new();
+    let mut drawn = HashSet::new();
+    for n in numbers {
+        if !drawn.insert(n) {
+            problems.push(Problem::at(&numbers_pos, format!("{} is drawn twice", n)));
+        }
+    }
+    for rows in boards {
+        if rows.len() != 5 {
+            problems.push(Problem::at(
+                &rows[0].0,
+                format!("board has {} rows instead of 5", rows.len()),
+            ));
+        }
+        let mut seen = HashSet::new();
+        for (pos, row) in rows {
+            if row.len() != 5 {
+                problems.push(Problem::at(
+                    &pos,
+                    format!("row has {} numbers instead of 5", row.len()),
+                ));
+            }
+            for n in row {
+                if !seen.insert(n) {
+                    problems.push(Problem::at(
+                        &pos,
+                        format!("{} appears twice on the board", n),
+                    ));
+                }
+            }
+        }
+    }
+    Ok(problems)
+}
+
+fn lint_line_segments(input: &str) -> Result<Vec<Problem>, ParseError> {
+    let segments = parse_all(lines_of(located(line_segment)), input)?;
+    Ok(segments
+        .iter()
+        .filter_map(|(pos, (a, b))| {
+            let (dx, dy) = ((b.x - a.x).abs(), (b.y - a.y).abs());
+            if a == b {
+                Some(Problem::at(pos, "line is a single point"))
+            } else if dx != 0 && dy != 0 && dx != dy {
+                Some(Problem::at(
+                    pos,
+                    "line is neither horizontal, vertical nor diagonal",
+                ))
+            } else {
+                None
+            }
+        })
+        .collect())
+}
+
+/// Number of segments of the digits 0-9.
+const SEGMENT_COUNTS: [usize; 10] = [6, 2, 5, 5, 4, 5, 6, 3, 7, 6];
+
+fn lint_seven_segment(input: &str) -> Result<Vec<Problem>, ParseError> {
+    let samples = parse_all(lines_of(located(seven_segment_sample)), input)?;
+    let sorted = |s: &str| s.chars().sorted().collect::<String>();
+
+    let mut problems = Vec::new();
+    for (pos, (patterns, outputs)) in samples {
+        let pattern_set = patterns.iter().map(|p| sorted(p)).collect::<HashSet<_>>();
+        if pattern_set.len() != 10 {
+            problems.push(Problem::at(&pos, "patterns are not all different"));
+        }
+        let counts = patterns.iter().map(|p| p.len()).sorted().collect_vec();
+        if counts != SEGMENT_COUNTS.iter().cloned().sorted().collect_vec() {
+            problems.push(Problem::at(
+                &pos,
+                "pattern lengths don't match the ten digits",
+            ));
+        }
+        for o in outputs {
+            if !pattern_set.contains(&sorted(o)) {
+                problems.push(Problem::at(
+                    &pos,
+                    format!("output {} is not one of the patterns", o),
+                ));
+            }
+        }
+    }
+    Ok(problems)
+}
+
+fn lint_caves(input: &str) -> Result<Vec<Problem>, ParseError> {
+    let edges = parse_all(lines_of(located(edge)), input)?;
+    let is_big = |name: &str| name.chars().all(|c| c.is_ascii_uppercase());
+
+    let mut problems = Vec::new();
+    for (pos, (a, b)) in &edges {
+        if a == b {
+            problems.push(Problem::at(pos, format!("{} is connected to itself", a)));
+        } else if is_big(a) && is_big(b) {
+            problems.push(Problem::at(
+                pos,
+                format!("big caves {} and {} are connected, paths are endless", a, b),
+            ));
+        }
+    }
+    for name in ["start", "end"] {
+        if !edges.iter().any(|(_, (a, b))| *a == name || *b == name) {
+            problems.push(Problem {
+                line: 1,
+                column: 1,
+                message: format!("there is no '{}' cave", name),
+            });
+        }
+    }
+    Ok(problems)
+}
+
+fn lint_folds(input: &str) -> Result<Vec<Problem>, ParseError> {
+    let (dots, folds) = parse_all(
+        separated_pair(
+            lines_of(located(coord2d)),
+            blank_lines,
+            lines_of(located(fold_instruction)),
+        ),
+        input,
+    )?;
+
+    let mut problems = Vec::new();
+    let mut folded = dots
+        .iter()
+        .map(|(pos, dot)| {
+            if dot.x < 0 || dot.y < 0 {
+                problems.push(Problem::at(pos, "dot outside of the paper"));
+            }
+            (pos, *dot)
+        })
+        .collect_vec();
+    for (fold_pos, fold) in &folds {
+        for (pos, dot) in folded.iter_mut() {
+            let (c, f) = match fold {
+                FoldInstruction::X(f) => (&mut dot.x, *f),
+                FoldInstruction::Y(f) => (&mut dot.y, *f),
+            };
+            if *c == f {
+                problems.push(Problem::at(
+                    pos,
+                    format!("dot lies on the fold at line {}", fold_pos.location_line()),
+                ));
+            } else if *c > f {
+                *c = 2 * f - *c;
+            }
+        }
+    }
+    Ok(problems)
+}
+
+fn lint_snailfish(input: &str) -> Result<Vec<Problem>, ParseError> {
+    /// Nesting depth of the innermost pair and the largest regular number.
+    fn depth_and_max(n: &SfNumber) -> (usize, i64) {
+        match n {
+            SfNumber::Pair(l, r) => {
+                let (dl, ml) = depth_and_max(l);
+                let (dr, mr) = depth_and_max(r);
+                (dl.max(dr) + 1, ml.max(mr))
+            }
+            SfNumber::Number(v) => (0, *v),
+        }
+    }
+
+    let numbers = parse_all(lines_of(located(snailfish_number)), input)?;
+    let mut problems = Vec::new();
+    for (pos, n) in numbers {
+        let (depth, max) = depth_and_max(&n);
+        if depth == 0 {
+            problems.push(Problem::at(&pos, "not a pair"));
+        }
+        if depth > 4 {
+            problems.push(Problem::at(
+                &pos,
+                format!(
+                    "pairs nested {} deep, reduced numbers have at most 4",
+                    depth
+                ),
+            ));
+        }
+        if max > 9 {
+            problems.push(Problem::at(
+                &pos,
+                format!("regular number {} should have been split", max),
+            ));
+        }
+    }
+    Ok(problems)
+}
+
+fn lint_scanners(input: &str) -> Result<Vec<Problem>, ParseError> {
+    let reports = parse_all(blocks_separated_by_blank_lines(located(scanner)), input)?;
+    let mut problems = Vec::new();
+    for (i, (pos, report)) in reports.iter().enumerate() {
+        if report.id != i as i64 {
+            problems.push(Problem::at(
+                pos,
+                format!("scanner {} where scanner {} was expected", report.id, i),
+            ));
+        }
+        if report.beacons.len() < 12 {
+            problems.push(Problem::at(
+                pos,
+                format!(
+                    "only {} beacons, at least 12 are needed to match scanners",
+                    report.beacons.len()
+                ),
+            ));
+        }
+        let mut seen = HashMap::new();
+        for b in &report.beacons {
+            *seen.entry(b).or_insert(0) += 1;
+        }
+        for (b, _) in seen.iter().filter(|(_, count)| **count > 1) {
+            problems.push(Problem::at(pos, format!("beacon {} is listed twice", b)));
+        }
+    }
+    Ok(problems)
+}
+
+fn lint_image(input: &str) -> Result<Vec<Problem>, ParseError> {
+    let ((filter_pos, filter), rows) = parse_all(
+        separated_pair(
+            located(pixel_row),
+            blank_lines,
+            lines_of(located(pixel_row)),
+        ),
+        input,
+    )?;
+
+    let mut problems = Vec::new();
+    if filter.len() != 512 {
+        problems.push(Problem::at(
+            &filter_pos,
+            format!(
+                "enhancement filter has {} pixels instead of 512",
+                filter.len()
+            ),
+        ));
+    }
+    let width = rows[0].1.len();
+    for (pos, row) in &rows {
+        if row.len() != width {
+            problems.push(Problem::at(
+                pos,
+                format!("row has {} pixels, the first one has {}", row.len(), width),
+            ));
+        }
+    }
+    Ok(problems)
+}
+
+fn lint_reboot_steps(input: &str) -> Result<Vec<Problem>, ParseError> {
+    let steps = parse_all(lines_of(located(reboot_step)), input)?;
+    let mut problems = Vec::new();
+    for (pos, step) in steps {
+        for (axis, range) in step.axes.iter().zip(step.ranges.iter()) {
+            if range.start() > range.end() {
+                problems.push(Problem::at(
+                    &pos,
+                    format!("{}={}..{} is empty", axis, range.start(), range.end()),
+                ));
+            }
+        }
+    }
+    Ok(problems)
+}
+
+#[test]
+fn test_lint() {
+    let problems = lint(
+        4,
+        "1,2,3,2
+
+        1 2 3 4 5
+        6 7 8 9 10
+        11 12 13 14 15
+        16 17 18 19 20
+        21 22 23 24 1
+
+        1 2 3 4 5
+        6 7 8 9 10
+        11 12 13 14 15
+        16 17 18 19",
+    )
+    .unwrap();
+    assert_eq!(
+        problems.iter().map(|p| p.to_string()).collect_vec(),
+        vec![
+            "1:1: 2 is drawn twice",
+            "7:9: 1 appears twice on the board",
+            "9:9: board has 4 rows instead of 5",
+            "12:9: row has 4 numbers instead of 5",
+        ]
+    );
+
+    let problems = lint(5, "0,9 -> 5,9\n    1,1 -> 3,2\n    2,2 -> 0,0").unwrap();
+    assert_eq!(problems.len(), 1);
+    assert_eq!((problems[0].line, problems[0].column), (2, 5));
+
+    let problems = lint(22, "on x=1..2,y=3..4,z=5..6\noff x=1..2,y=4..3,z=5..6").unwrap();
+    assert_eq!(problems[0].to_string(), "2:1: y=4..3 is empty");
+
+    let problems = lint(19, "--- scanner 0 ---\n1,2,3\n\n--- scanner 2 ---\n1,2,3").unwrap();
+    assert_eq!(problems.len(), 3);
+    assert_eq!(
+        problems[1].to_string(),
+        "4:1: scanner 2 where scanner 1 was expected"
+    );
+
+    let problems = lint(20, "#.#\n\n#..\n#.").unwrap();
+    assert_eq!(problems.len(), 2);
+
+    let problems = lint(13, "6,10\n0,14\n0,7\n\nfold along y=7\nfold along x=5").unwrap();
+    assert_eq!(
+        problems[0].to_string(),
+        "3:1: dot lies on the fold at line 5"
+    );
+
+    // parse errors are reported where parsing stopped
+    let problems = lint(2, "forward 5\n  down x").unwrap();
+    assert_eq!((problems[0].line, problems[0].column), (2, 3));
+
+    assert_eq!(lint(1, "1\n2\n"), None);
+}
+
+#[test]
+fn test_lint_generated() {
+    use crate::generators;
+
+    assert_eq!(lint(4, &generators::bingo(1, 5).input), Some(vec![]));
+    assert_eq!(
+        lint(5, &generators::vent_lines(1, 50, 30).input),
+        Some(vec![])
+    );
+    assert_eq!(
+        lint(8, &generators::seven_segment(1, 10).input),
+        Some(vec![])
+    );
+    assert_eq!(
+        lint(12, &generators::cave_graph(1, 5, 2, 8).input),
+        Some(vec![])
+    );
+    assert_eq!(
+        lint(18, &generators::snailfish_list(1, 5).input),
+        Some(vec![])
+    );
+    assert_eq!(
+        lint(19, &generators::scanner_clouds(1, 3).0.input),
+        Some(vec![])
+    );
+    assert_eq!(
+        lint(22, &generators::reboot_steps(1, 10, 60).input),
+        Some(vec![])
+    );
+}
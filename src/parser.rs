@@ -1,53 +1,467 @@
-use std::ops::RangeInclusive;
+use std::{
+    fmt,
+    io::{self, BufRead},
+    ops::RangeInclusive,
+    str::FromStr,
+};
 
-use itertools::Itertools;
 use nom::{
     branch::alt,
     bytes::complete::tag,
-    character::complete::{alpha1, char, multispace0, multispace1, one_of, space0, space1},
-    combinator::{map, opt, recognize},
-    multi::{count, many0, many1, many_m_n, separated_list0, separated_list1},
+    character::complete::{
+        alpha1, alphanumeric1, char, line_ending, multispace0, one_of, satisfy, space0, space1,
+    },
+    combinator::{all_consuming, map, map_res, opt, recognize, value, verify},
+    error::{ErrorKind, ParseError as _},
+    multi::{many0, many1, many_m_n, separated_list0, separated_list1},
     sequence::{delimited, preceded, separated_pair, terminated, tuple},
-    IResult,
+    Err, IResult,
 };
+use nom_locate::{position, LocatedSpan};
+use num_bigint::BigInt;
 
-use crate::{BingoBoard, SfNumber, Vec2, Vec3};
+use crate::{boxn::BoxN, BingoBoard, Cube, SfNumber, Vec2, Vec3};
 
 // use crate::{Claim, RecordTimestamp, RecordType, Rect};
 
-pub fn float(input: &str) -> IResult<&str, &str> {
-    alt((
-        // Case one: .42
-        recognize(tuple((
-            char('.'),
-            decimal,
-            opt(tuple((one_of("eE"), opt(one_of("+-")), decimal))),
-        ))), // Case two: 42e42 and 42.42e42
-        recognize(tuple((
-            decimal,
-            opt(preceded(char('.'), decimal)),
-            one_of("eE"),
-            opt(one_of("+-")),
-            decimal,
-        ))), // Case three: 42. and 42.42
-        recognize(tuple((decimal, char('.'), opt(decimal)))),
-    ))(input)
+/// Parser input that keeps track of where in the original text it is.
+pub type Span<'a> = LocatedSpan<&'a str>;
+
+/// Where (and why) 'parse_all' gave up. Lines and columns start at 1.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: u32,
+    pub column: usize,
+    pub kind: ErrorKind,
+}
+
+impl ParseError {
+    fn at(input: Span<'_>, kind: ErrorKind) -> ParseError {
+        ParseError {
+            line: input.location_line(),
+            column: input.get_utf8_column(),
+            kind,
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "parse error at line {}, column {} ({})",
+            self.line,
+            self.column,
+            self.kind.description()
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// Run 'parser' on the whole of 'input'. Whitespace (including blank lines) before and after the
+/// parsed value is ignored, anything else left over is an error.
+pub fn parse_all<'a, T, P>(parser: P, input: &'a str) -> Result<T, ParseError>
+where
+    P: FnMut(Span<'a>) -> IResult<Span<'a>, T>,
+{
+    match all_consuming(delimited(multispace0, parser, multispace0))(Span::new(input)) {
+        Ok((_, value)) => Ok(value),
+        Err(Err::Error(e)) | Err(Err::Failure(e)) => Err(ParseError::at(e.input, e.code)),
+        Err(Err::Incomplete(_)) => Err(ParseError::at(Span::new(input), ErrorKind::Eof)),
+    }
+}
+
+/// Line break between two records: trailing spaces, the newline and the indentation of the next
+/// line.
+fn line_break(input: Span<'_>) -> IResult<Span<'_>, Span<'_>> {
+    recognize(tuple((space0, line_ending, space0)))(input)
+}
+
+/// One or more blank (or whitespace only) lines between two blocks.
+pub fn blank_lines(input: Span<'_>) -> IResult<Span<'_>, Span<'_>> {
+    recognize(tuple((
+        space0,
+        line_ending,
+        many1(tuple((space0, line_ending))),
+        space0,
+    )))(input)
+}
+
+/// One record per line, parsed by 'p'. Indentation and trailing spaces are skipped, an empty line
+/// ends the list.
+pub fn lines_of<'a, T, P>(p: P) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, Vec<T>>
+where
+    P: FnMut(Span<'a>) -> IResult<Span<'a>, T>,
+{
+    delimited(space0, separated_list1(line_break, p), space0)
 }
 
-pub fn signed_decimal(input: &str) -> IResult<&str, i64> {
-    alt((recognize(tuple((opt(one_of("+-")), decimal))), decimal))(input)
-        .map(|x| (x.0, x.1.parse().unwrap()))
+/// 'p' together with the position where it started, for reporting problems with the parsed value.
+pub fn located<'a, T, P>(p: P) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, (Span<'a>, T)>
+where
+    P: FnMut(Span<'a>) -> IResult<Span<'a>, T>,
+{
+    tuple((position, p))
 }
 
-pub fn decimal(input: &str) -> IResult<&str, &str> {
-    recognize(many1(terminated(one_of("0123456789"), many0(char('_')))))(input)
+/// Blocks parsed by 'p', separated by one or more blank lines.
+pub fn blocks_separated_by_blank_lines<'a, T, P>(
+    p: P,
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, Vec<T>>
+where
+    P: FnMut(Span<'a>) -> IResult<Span<'a>, T>,
+{
+    delimited(space0, separated_list1(blank_lines, p), space0)
 }
 
-pub fn signed_decimal_list(input: &str) -> IResult<&str, Vec<i64>> {
-    separated_list0(multispace1, signed_decimal)(input)
+/// Error for a single record of 'Records'. Lines are counted from the start of the stream.
+#[derive(Debug)]
+pub enum RecordError {
+    Io(io::Error),
+    Parse(ParseError),
 }
 
-pub fn signed_decimal_comma_separated_list(input: &str) -> IResult<&str, Vec<i64>> {
+impl fmt::Display for RecordError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RecordError::Io(e) => write!(f, "read error: {}", e),
+            RecordError::Parse(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl std::error::Error for RecordError {}
+
+pub type RecordParser<T> = for<'a> fn(Span<'a>) -> IResult<Span<'a>, T>;
+
+/// Parses records from a 'BufRead' one at a time, so only the current record is held in memory.
+/// A record that fails to parse yields an error and iteration goes on with the next one.
+pub struct Records<R, T> {
+    reader: R,
+    parser: RecordParser<T>,
+    blocks: bool,
+    line: u32,
+    buf: String,
+    record: String,
+    failed: bool,
+}
+
+impl<R: BufRead, T> Records<R, T> {
+    /// One record per line, blank lines are skipped.
+    pub fn lines(reader: R, parser: RecordParser<T>) -> Self {
+        Self::new(reader, parser, false)
+    }
+
+    /// One record per block of lines, blocks are separated by blank lines.
+    pub fn blocks(reader: R, parser: RecordParser<T>) -> Self {
+        Self::new(reader, parser, true)
+    }
+
+    fn new(reader: R, parser: RecordParser<T>, blocks: bool) -> Self {
+        Records {
+            reader,
+            parser,
+            blocks,
+            line: 0,
+            buf: String::new(),
+            record: String::new(),
+            failed: false,
+        }
+    }
+}
+
+impl<R: BufRead, T> Iterator for Records<R, T> {
+    type Item = Result<T, RecordError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        self.record.clear();
+        let mut first_line = 0;
+        loop {
+            self.buf.clear();
+            match self.reader.read_line(&mut self.buf) {
+                Ok(0) => break,
+                Ok(_) => self.line += 1,
+                Err(e) => {
+                    self.failed = true;
+                    return Some(Err(RecordError::Io(e)));
+                }
+            }
+            if self.buf.trim().is_empty() {
+                if self.record.is_empty() {
+                    continue;
+                }
+                break;
+            }
+            if self.record.is_empty() {
+                first_line = self.line;
+            }
+            self.record.push_str(&self.buf);
+            if !self.blocks {
+                break;
+            }
+        }
+        if self.record.is_empty() {
+            return None;
+        }
+        Some(parse_all(self.parser, &self.record).map_err(|e| {
+            RecordError::Parse(ParseError {
+                line: first_line + e.line - 1,
+                ..e
+            })
+        }))
+    }
+}
+
+pub fn float(input: Span<'_>) -> IResult<Span<'_>, &str> {
+    map(
+        alt((
+            // Case one: .42
+            recognize(tuple((
+                char('.'),
+                decimal,
+                opt(tuple((one_of("eE"), opt(one_of("+-")), decimal))),
+            ))), // Case two: 42e42 and 42.42e42
+            recognize(tuple((
+                decimal,
+                opt(preceded(char('.'), decimal)),
+                one_of("eE"),
+                opt(one_of("+-")),
+                decimal,
+            ))), // Case three: 42. and 42.42
+            recognize(tuple((decimal, char('.'), opt(decimal)))),
+        )),
+        |s: Span<'_>| *s.fragment(),
+    )(input)
+}
+
+/// An integer with an optional sign out of 'signs'. Numbers that don't fit into 'T' are a
+/// 'Failure' (ErrorKind::MapRes) pointing at the number, so they are reported instead of skipped.
+fn integer<'a, T: FromStr>(signs: &'static str) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, T> {
+    move |input| {
+        let (rest, digits) = recognize(tuple((opt(one_of(signs)), decimal)))(input)?;
+        match digits.fragment().replace('_', "").parse() {
+            Ok(n) => Ok((rest, n)),
+            Err(_) => Err(Err::Failure(nom::error::Error::from_error_kind(
+                input,
+                ErrorKind::MapRes,
+            ))),
+        }
+    }
+}
+
+pub fn signed_decimal(input: Span<'_>) -> IResult<Span<'_>, i64> {
+    integer("+-")(input)
+}
+
+pub fn signed_decimal_i128(input: Span<'_>) -> IResult<Span<'_>, i128> {
+    integer("+-")(input)
+}
+
+pub fn unsigned_decimal_u64(input: Span<'_>) -> IResult<Span<'_>, u64> {
+    integer("+")(input)
+}
+
+pub fn big_decimal(input: Span<'_>) -> IResult<Span<'_>, BigInt> {
+    integer("+-")(input)
+}
+
+#[test]
+fn test_integers() {
+    assert_eq!(parse_all(signed_decimal, "+1_000"), Ok(1000));
+    assert_eq!(parse_all(signed_decimal, "-42"), Ok(-42));
+    assert_eq!(
+        parse_all(signed_decimal, "9223372036854775807"),
+        Ok(i64::MAX)
+    );
+    let err = parse_all(signed_decimal_list, "1\n  99999999999999999999").unwrap_err();
+    assert_eq!((err.line, err.column, err.kind), (2, 3, ErrorKind::MapRes));
+
+    assert_eq!(
+        parse_all(signed_decimal_i128, "99_999_999_999_999_999_999"),
+        Ok(99_999_999_999_999_999_999)
+    );
+    assert_eq!(
+        parse_all(unsigned_decimal_u64, "18446744073709551615"),
+        Ok(u64::MAX)
+    );
+    assert!(parse_all(unsigned_decimal_u64, "-1").is_err());
+    assert_eq!(
+        parse_all(
+            big_decimal,
+            "-1_000_000_000_000_000_000_000_000_000_000_000_000_000"
+        ),
+        Ok(-BigInt::from(10u8).pow(39))
+    );
+}
+
+pub fn decimal(input: Span<'_>) -> IResult<Span<'_>, &str> {
+    map(
+        recognize(many1(terminated(one_of("0123456789"), many0(char('_'))))),
+        |s: Span<'_>| *s.fragment(),
+    )(input)
+}
+
+/// A float (or plain integer) without sign, see 'float'.
+pub fn float_value(input: Span<'_>) -> IResult<Span<'_>, f64> {
+    map_res(alt((float, decimal)), |s| s.replace('_', "").parse())(input)
+}
+
+pub fn signed_float_value(input: Span<'_>) -> IResult<Span<'_>, f64> {
+    map_res(
+        recognize(tuple((opt(one_of("+-")), alt((float, decimal))))),
+        |s: Span<'_>| s.fragment().replace('_', "").parse(),
+    )(input)
+}
+
+/// An exact decimal number: 'value' / 10^'scale'.
+///
+/// Equality compares the numbers, not the representation: "1.50" == "1.5".
+#[derive(Debug, Clone, Copy, Eq)]
+pub struct FixedPoint {
+    pub value: i128,
+    pub scale: u32,
+}
+
+impl FixedPoint {
+    pub fn to_f64(&self) -> f64 {
+        self.value as f64 / 10f64.powi(self.scale as i32)
+    }
+
+    /// The same number as a multiple of 10^-'scale'. None if it can't be represented exactly (or
+    /// overflows).
+    pub fn with_scale(&self, scale: u32) -> Option<FixedPoint> {
+        let value = if scale >= self.scale {
+            self.value
+                .checked_mul(10i128.checked_pow(scale - self.scale)?)?
+        } else {
+            let div = 10i128.checked_pow(self.scale - scale)?;
+            if self.value % div != 0 {
+                return None;
+            }
+            self.value / div
+        };
+        Some(FixedPoint { value, scale })
+    }
+}
+
+impl PartialEq for FixedPoint {
+    fn eq(&self, other: &Self) -> bool {
+        // if the value with fewer digits overflows at the larger scale, it can't be equal
+        let scale = self.scale.max(other.scale);
+        match (self.with_scale(scale), other.with_scale(scale)) {
+            (Some(a), Some(b)) => a.value == b.value,
+            _ => false,
+        }
+    }
+}
+
+fn fixed_point_with_sign<'a>(
+    signs: &'static str,
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, FixedPoint> {
+    move |input| {
+        let (rest, (sign, (int, frac))) = tuple((
+            opt(one_of(signs)),
+            alt((
+                tuple((decimal, opt(preceded(char('.'), decimal)))),
+                map(preceded(char('.'), decimal), |frac| ("", Some(frac))),
+            )),
+        ))(input)?;
+        let frac = frac.unwrap_or("").replace('_', "");
+        let digits = format!("{}{}{}", sign.unwrap_or('+'), int.replace('_', ""), frac);
+        match digits.parse() {
+            Ok(value) => Ok((
+                rest,
+                FixedPoint {
+                    value,
+                    scale: frac.len() as u32,
+                },
+            )),
+            Err(_) => Err(Err::Failure(nom::error::Error::from_error_kind(
+                input,
+                ErrorKind::MapRes,
+            ))),
+        }
+    }
+}
+
+/// A decimal number like 12.25 or .5, parsed exactly. Overflow is a 'Failure' like in 'integer'.
+pub fn fixed_point(input: Span<'_>) -> IResult<Span<'_>, FixedPoint> {
+    fixed_point_with_sign("+")(input)
+}
+
+pub fn signed_fixed_point(input: Span<'_>) -> IResult<Span<'_>, FixedPoint> {
+    fixed_point_with_sign("+-")(input)
+}
+
+#[test]
+fn test_float_value() {
+    assert_eq!(parse_all(float_value, "42"), Ok(42.0));
+    assert_eq!(parse_all(float_value, "1_000.5"), Ok(1000.5));
+    assert_eq!(parse_all(float_value, ".25e2"), Ok(25.0));
+    assert!(parse_all(float_value, "-1.5").is_err());
+    assert_eq!(parse_all(signed_float_value, "-1.5"), Ok(-1.5));
+    assert_eq!(parse_all(signed_float_value, "+2.5E-1"), Ok(0.25));
+}
+
+#[test]
+fn test_fixed_point() {
+    let x = parse_all(signed_fixed_point, "-12.250").unwrap();
+    assert_eq!(
+        x,
+        FixedPoint {
+            value: -12250,
+            scale: 3
+        }
+    );
+    assert_eq!(x.to_f64(), -12.25);
+    assert_eq!(
+        x.with_scale(2),
+        Some(FixedPoint {
+            value: -1225,
+            scale: 2
+        })
+    );
+    assert_eq!(x.with_scale(1), None);
+    assert_eq!(
+        parse_all(fixed_point, ".5"),
+        Ok(FixedPoint { value: 5, scale: 1 })
+    );
+    assert_eq!(
+        parse_all(fixed_point, "7"),
+        Ok(FixedPoint { value: 7, scale: 0 })
+    );
+    assert_eq!(
+        parse_all(fixed_point, "1.50"),
+        parse_all(fixed_point, "1.5")
+    );
+    assert_eq!(
+        parse_all(signed_fixed_point, "-2.000"),
+        parse_all(signed_fixed_point, "-2")
+    );
+    assert_ne!(
+        parse_all(fixed_point, "1.05"),
+        parse_all(fixed_point, "1.5")
+    );
+    let big = FixedPoint {
+        value: i128::MAX,
+        scale: 0,
+    };
+    assert_ne!(big, FixedPoint { value: 1, scale: 1 });
+    assert_eq!(big, big);
+    assert!(parse_all(fixed_point, "-7").is_err());
+    let err = parse_all(fixed_point, "1.00000000000000000000000000000000000000001").unwrap_err();
+    assert_eq!(err.kind, ErrorKind::MapRes);
+}
+
+pub fn signed_decimal_list(input: Span<'_>) -> IResult<Span<'_>, Vec<i64>> {
+    lines_of(signed_decimal)(input)
+}
+
+pub fn signed_decimal_comma_separated_list(input: Span<'_>) -> IResult<Span<'_>, Vec<i64>> {
     separated_list0(char(','), signed_decimal)(input)
 }
 
@@ -57,108 +471,216 @@ pub enum SubmarineCommand {
     Down(i64),
     Forward(i64),
 }
-pub fn submarine_command(input: &str) -> IResult<&str, SubmarineCommand> {
-    fn up(input: &str) -> IResult<&str, SubmarineCommand> {
-        preceded(tuple((tag("up"), multispace1)), signed_decimal)(input)
+
+impl fmt::Display for SubmarineCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SubmarineCommand::Up(i) => write!(f, "up {}", i),
+            SubmarineCommand::Down(i) => write!(f, "down {}", i),
+            SubmarineCommand::Forward(i) => write!(f, "forward {}", i),
+        }
+    }
+}
+
+impl FromStr for SubmarineCommand {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_all(submarine_command, s)
+    }
+}
+pub fn submarine_command(input: Span<'_>) -> IResult<Span<'_>, SubmarineCommand> {
+    fn up(input: Span<'_>) -> IResult<Span<'_>, SubmarineCommand> {
+        preceded(tuple((tag("up"), space1)), signed_decimal)(input)
             .map(|(input, i)| (input, SubmarineCommand::Up(i)))
     }
-    fn down(input: &str) -> IResult<&str, SubmarineCommand> {
-        preceded(tuple((tag("down"), multispace1)), signed_decimal)(input)
+    fn down(input: Span<'_>) -> IResult<Span<'_>, SubmarineCommand> {
+        preceded(tuple((tag("down"), space1)), signed_decimal)(input)
             .map(|(input, i)| (input, SubmarineCommand::Down(i)))
     }
-    fn forward(input: &str) -> IResult<&str, SubmarineCommand> {
-        preceded(tuple((tag("forward"), multispace1)), signed_decimal)(input)
+    fn forward(input: Span<'_>) -> IResult<Span<'_>, SubmarineCommand> {
+        preceded(tuple((tag("forward"), space1)), signed_decimal)(input)
             .map(|(input, i)| (input, SubmarineCommand::Forward(i)))
     }
     alt((up, down, forward))(input)
 }
 
-pub fn submarine_command_list(input: &str) -> IResult<&str, Vec<SubmarineCommand>> {
-    separated_list0(multispace1, submarine_command)(input)
+pub fn submarine_commands<R: BufRead>(reader: R) -> Records<R, SubmarineCommand> {
+    Records::lines(reader, submarine_command)
+}
+
+pub fn submarine_command_list(input: Span<'_>) -> IResult<Span<'_>, Vec<SubmarineCommand>> {
+    lines_of(submarine_command)(input)
 }
 
 #[test]
 fn submarine_test() {
     assert_eq!(
-        submarine_command("up 10").unwrap(),
-        ("", SubmarineCommand::Up(10))
+        parse_all(submarine_command, "up 10"),
+        Ok(SubmarineCommand::Up(10))
+    );
+    assert_eq!(
+        parse_all(
+            submarine_command_list,
+            "forward 5\n    down 5\n    up 3\n    "
+        ),
+        Ok(vec![
+            SubmarineCommand::Forward(5),
+            SubmarineCommand::Down(5),
+            SubmarineCommand::Up(3)
+        ])
     );
+    let err = parse_all(
+        submarine_command_list,
+        "forward 5\n    down 5\n    sideways 3",
+    )
+    .unwrap_err();
+    assert_eq!((err.line, err.column), (3, 5));
+}
+
+pub fn bingo_board(input: Span<'_>) -> IResult<Span<'_>, BingoBoard> {
+    fn bingo_line(input: Span<'_>) -> IResult<Span<'_>, Vec<i64>> {
+        verify(separated_list1(space1, signed_decimal), |row: &Vec<i64>| {
+            row.len() == 5
+        })(input)
+    }
+    map(
+        verify(lines_of(bingo_line), |rows: &Vec<Vec<i64>>| rows.len() == 5),
+        BingoBoard::new,
+    )(input)
 }
 
-pub fn bingo_board(input: &str) -> IResult<&str, BingoBoard> {
-    fn bingo_line(input: &str) -> IResult<&str, Vec<i64>> {
-        count(delimited(multispace0, signed_decimal, multispace0), 5)(input)
+impl FromStr for BingoBoard {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_all(bingo_board, s)
     }
-    let (input, lines) = count(delimited(multispace0, bingo_line, multispace0), 5)(input)?;
-    Ok((input, BingoBoard::new(lines)))
 }
 
-pub fn bingo_board_list(input: &str) -> IResult<&str, Vec<BingoBoard>> {
-    many0(bingo_board)(input)
+pub fn bingo_board_list(input: Span<'_>) -> IResult<Span<'_>, Vec<BingoBoard>> {
+    blocks_separated_by_blank_lines(bingo_board)(input)
+}
+
+/// The drawn numbers followed by the boards.
+pub fn bingo_game(input: Span<'_>) -> IResult<Span<'_>, (Vec<i64>, Vec<BingoBoard>)> {
+    separated_pair(
+        signed_decimal_comma_separated_list,
+        blank_lines,
+        bingo_board_list,
+    )(input)
 }
 
 #[test]
 fn bingo_test() {
-    println!(
-        "{:?}",
-        bingo_board_list(
-            "
+    let (numbers, boards) = parse_all(
+        bingo_game,
+        "
+        7,4,9,5,11
 
-88 29 95 98 57
-49 36  6 23 83
-18  5 45 40 44
-62 81 74 99 87
-46 56 35 21 52
+        88 29 95 98 57
+        49 36  6 23 83
+        18  5 45 40 44
+        62 81 74 99 87
+        46 56 35 21 52
 
 
-49 11 72 87 56
-40 94 71 70  3
-65  2 90 64 63
-32 79 24 44 55
-58 53 35 77 60
+        49 11 72 87 56
+        40 94 71 70  3
+        65  2 90 64 63
+        32 79 24 44 55
+        58 53 35 77 60
 
-    "
-        )
+    ",
     )
+    .unwrap();
+    assert_eq!(numbers, vec![7, 4, 9, 5, 11]);
+    assert_eq!(boards.len(), 2);
+    assert_eq!(boards[1].count_remaining(), 1354);
+}
+
+/// A row of '#' (true) and '.' (false) pixels.
+pub fn pixel_row(input: Span<'_>) -> IResult<Span<'_>, Vec<bool>> {
+    many1(map(one_of("#."), |c| c == '#'))(input)
 }
 
-pub fn coord2d(input: &str) -> IResult<&str, Vec2> {
+/// The enhancement filter and the image of day 20, separated by a blank line.
+pub fn image_enhancement(input: Span<'_>) -> IResult<Span<'_>, (Vec<bool>, Vec<Vec<bool>>)> {
+    separated_pair(pixel_row, blank_lines, lines_of(pixel_row))(input)
+}
+
+/// A row of single digit numbers like "2199943210".
+pub fn digit_row(input: Span<'_>) -> IResult<Span<'_>, Vec<i64>> {
+    many1(map(one_of("0123456789"), |c| (c as u8 - b'0') as i64))(input)
+}
+
+pub fn digit_field(input: Span<'_>) -> IResult<Span<'_>, Vec<Vec<i64>>> {
+    lines_of(digit_row)(input)
+}
+
+#[test]
+fn test_pixels_and_digits() {
+    let (filter, image) =
+        parse_all(image_enhancement, "..#.#\n    \n    #..#.\n    #....").unwrap();
+    assert_eq!(filter, vec![false, false, true, false, true]);
+    assert_eq!(image.len(), 2);
+    assert_eq!(image[1], vec![true, false, false, false, false]);
+
+    assert_eq!(
+        parse_all(digit_field, "2199\n    3987"),
+        Ok(vec![vec![2, 1, 9, 9], vec![3, 9, 8, 7]])
+    );
+    let err = parse_all(digit_field, "2199\n    39a7").unwrap_err();
+    assert_eq!((err.line, err.column), (2, 7));
+}
+
+pub fn coord2d(input: Span<'_>) -> IResult<Span<'_>, Vec2> {
     let (input, (x, y)) = separated_pair(signed_decimal, char(','), signed_decimal)(input)?;
 
     Ok((input, Vec2 { x, y }))
 }
 
-pub fn coord2d_list(input: &str) -> IResult<&str, Vec<Vec2>> {
-    separated_list0(multispace0, coord2d)(input)
+pub fn coord2d_list(input: Span<'_>) -> IResult<Span<'_>, Vec<Vec2>> {
+    lines_of(coord2d)(input)
 }
 
-pub fn line_segment(input: &str) -> IResult<&str, (Vec2, Vec2)> {
+pub fn line_segment(input: Span<'_>) -> IResult<Span<'_>, (Vec2, Vec2)> {
     let (input, (p1, p2)) = separated_pair(coord2d, tag(" -> "), coord2d)(input)?;
     Ok((input, (p1, p2)))
 }
 
-pub fn line_segment_list(input: &str) -> IResult<&str, Vec<(Vec2, Vec2)>> {
-    separated_list0(multispace1, line_segment)(input)
+pub fn line_segments<R: BufRead>(reader: R) -> Records<R, (Vec2, Vec2)> {
+    Records::lines(reader, line_segment)
+}
+
+pub fn line_segment_list(input: Span<'_>) -> IResult<Span<'_>, Vec<(Vec2, Vec2)>> {
+    lines_of(line_segment)(input)
 }
 
 #[test]
 fn line_test() {
     assert_eq!(
-        line_segment("0,9 -> 5,9").unwrap(),
-        ("", (Vec2 { x: 0, y: 9 }, Vec2 { x: 5, y: 9 }))
+        parse_all(line_segment, "0,9 -> 5,9"),
+        Ok((Vec2 { x: 0, y: 9 }, Vec2 { x: 5, y: 9 }))
     );
+    let err = parse_all(line_segment_list, "0,9 -> 5,9\n  8,0 -> 0,8\n  9,4 => 3,4").unwrap_err();
+    assert_eq!((err.line, err.column), (3, 3));
 }
 
-pub fn seven_segment_sample(input: &str) -> IResult<&str, (Vec<&str>, Vec<&str>)> {
-    fn ag_string(input: &str) -> IResult<&str, &str> {
-        recognize(many1(one_of("abcdefg")))(input)
+type SevenSegmentSample<'a> = (Vec<&'a str>, Vec<&'a str>);
+
+pub fn seven_segment_sample(input: Span<'_>) -> IResult<Span<'_>, SevenSegmentSample<'_>> {
+    fn ag_string(input: Span<'_>) -> IResult<Span<'_>, &str> {
+        map(recognize(many1(one_of("abcdefg"))), |s: Span<'_>| {
+            *s.fragment()
+        })(input)
     }
 
-    fn ag_string_list_10(input: &str) -> IResult<&str, Vec<&str>> {
+    fn ag_string_list_10(input: Span<'_>) -> IResult<Span<'_>, Vec<&str>> {
         many_m_n(10, 10, delimited(space0, ag_string, space0))(input)
     }
 
-    fn ag_string_list_4(input: &str) -> IResult<&str, Vec<&str>> {
+    fn ag_string_list_4(input: Span<'_>) -> IResult<Span<'_>, Vec<&str>> {
         many_m_n(4, 4, delimited(space0, ag_string, space0))(input)
     }
 
@@ -169,8 +691,21 @@ pub fn seven_segment_sample(input: &str) -> IResult<&str, (Vec<&str>, Vec<&str>)
     )(input)
 }
 
-pub fn seven_segment_sample_list(input: &str) -> IResult<&str, Vec<(Vec<&str>, Vec<&str>)>> {
-    separated_list0(multispace1, seven_segment_sample)(input)
+/// A line of day 10: nothing but brackets of the four kinds.
+pub fn bracket_line(input: Span<'_>) -> IResult<Span<'_>, &str> {
+    map(recognize(many1(one_of("()[]{}<>"))), |s: Span<'_>| {
+        *s.fragment()
+    })(input)
+}
+
+pub fn bracket_line_list(input: Span<'_>) -> IResult<Span<'_>, Vec<&str>> {
+    lines_of(bracket_line)(input)
+}
+
+pub fn seven_segment_sample_list(
+    input: Span<'_>,
+) -> IResult<Span<'_>, Vec<SevenSegmentSample<'_>>> {
+    lines_of(seven_segment_sample)(input)
 }
 
 #[test]
@@ -178,47 +713,41 @@ fn test_seven_segment() {
     // fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf |
     // gebdcfa ecba ca fadegcb
     assert_eq!(
-        seven_segment_sample(
+        parse_all(
+            seven_segment_sample,
             "fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb"
-        )
-        .unwrap(),
-        (
-            "",
+        ),
+        Ok((
+            vec![
+                "fgeab", "ca", "afcebg", "bdacfeg", "cfaedg", "gcfdb", "baec", "bfadeg", "bafgc",
+                "acf"
+            ],
+            vec!["gebdcfa", "ecba", "ca", "fadegcb"]
+        ))
+    );
+
+    assert_eq!(
+        parse_all(
+            seven_segment_sample_list,
+            "fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
+            fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb"
+        ),
+        Ok(vec![
             (
                 vec![
                     "fgeab", "ca", "afcebg", "bdacfeg", "cfaedg", "gcfdb", "baec", "bfadeg",
                     "bafgc", "acf"
                 ],
                 vec!["gebdcfa", "ecba", "ca", "fadegcb"]
-            )
-        )
-    );
-
-    assert_eq!(
-        seven_segment_sample_list(
-            "fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb
-            fgeab ca afcebg bdacfeg cfaedg gcfdb baec bfadeg bafgc acf | gebdcfa ecba ca fadegcb"
-        )
-        .unwrap(),
-        (
-            "",
-            vec![
-                (
-                    vec![
-                        "fgeab", "ca", "afcebg", "bdacfeg", "cfaedg", "gcfdb", "baec", "bfadeg",
-                        "bafgc", "acf"
-                    ],
-                    vec!["gebdcfa", "ecba", "ca", "fadegcb"]
-                ),
-                (
-                    vec![
-                        "fgeab", "ca", "afcebg", "bdacfeg", "cfaedg", "gcfdb", "baec", "bfadeg",
-                        "bafgc", "acf"
-                    ],
-                    vec!["gebdcfa", "ecba", "ca", "fadegcb"]
-                ),
-            ]
-        )
+            ),
+            (
+                vec![
+                    "fgeab", "ca", "afcebg", "bdacfeg", "cfaedg", "gcfdb", "baec", "bfadeg",
+                    "bafgc", "acf"
+                ],
+                vec!["gebdcfa", "ecba", "ca", "fadegcb"]
+            ),
+        ])
     );
 }
 
@@ -228,48 +757,125 @@ pub enum FoldInstruction {
     Y(i64),
 }
 
-pub fn fold_instruction(input: &str) -> IResult<&str, FoldInstruction> {
-    let (input, (fi, num)) = preceded(
+impl fmt::Display for FoldInstruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FoldInstruction::X(i) => write!(f, "fold along x={}", i),
+            FoldInstruction::Y(i) => write!(f, "fold along y={}", i),
+        }
+    }
+}
+
+impl FromStr for FoldInstruction {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_all(fold_instruction, s)
+    }
+}
+
+pub fn fold_instruction(input: Span<'_>) -> IResult<Span<'_>, FoldInstruction> {
+    preceded(
         tag("fold along "),
-        separated_pair(alt((tag("x"), tag("y"))), char('='), signed_decimal),
-    )(input)?;
-    Ok((
-        input,
-        match fi {
-            "x" => FoldInstruction::X(num),
-            "y" => FoldInstruction::Y(num),
-            _ => panic!("bad direction in fold instruction"),
-        },
-    ))
+        alt((
+            map(preceded(tag("x="), signed_decimal), FoldInstruction::X),
+            map(preceded(tag("y="), signed_decimal), FoldInstruction::Y),
+        )),
+    )(input)
 }
 
-pub fn fold_instruction_list(input: &str) -> IResult<&str, Vec<FoldInstruction>> {
-    separated_list0(multispace1, fold_instruction)(input)
+pub fn fold_instruction_list(input: Span<'_>) -> IResult<Span<'_>, Vec<FoldInstruction>> {
+    lines_of(fold_instruction)(input)
 }
 
-pub fn coords_and_fold(input: &str) -> IResult<&str, (Vec<Vec2>, Vec<FoldInstruction>)> {
-    separated_pair(coord2d_list, multispace1, fold_instruction_list)(input)
+pub fn coords_and_fold(input: Span<'_>) -> IResult<Span<'_>, (Vec<Vec2>, Vec<FoldInstruction>)> {
+    separated_pair(coord2d_list, blank_lines, fold_instruction_list)(input)
 }
 
 #[test]
 fn test_fold() {
-    let (_, fi) = fold_instruction("fold along y=7").unwrap();
-    assert_eq!(fi, FoldInstruction::Y(7));
+    assert_eq!(
+        parse_all(fold_instruction, "fold along y=7"),
+        Ok(FoldInstruction::Y(7))
+    );
 
-    let (input, x) = coords_and_fold(
+    let (coords, folds) = parse_all(
+        coords_and_fold,
         "1,10
     2,14
     8,10
     9,0
-    
+
     fold along y=7
     fold along x=5",
     )
     .unwrap();
-    println!("{:?}", x);
+    assert_eq!(coords.len(), 4);
+    assert_eq!(folds, vec![FoldInstruction::Y(7), FoldInstruction::X(5)]);
+
+    // coordinates have to be one per line
+    assert!(parse_all(coord2d_list, "1,10 2,14").is_err());
 }
 
-pub fn snailfish_number(input: &str) -> IResult<&str, SfNumber> {
+/// The pair to insert between and the element to insert.
+pub type InsertionRule = ([char; 2], char);
+
+/// A pair insertion rule like "CH -> B".
+pub fn insertion_rule(input: Span<'_>) -> IResult<Span<'_>, InsertionRule> {
+    let element = || satisfy(|c| c.is_ascii_uppercase());
+    map(
+        separated_pair(tuple((element(), element())), tag(" -> "), element()),
+        |((a, b), c)| ([a, b], c),
+    )(input)
+}
+
+/// The polymer template and the pair insertion rules of day 14.
+pub fn polymer_instructions(input: Span<'_>) -> IResult<Span<'_>, (&str, Vec<InsertionRule>)> {
+    separated_pair(
+        map(alpha1, |s: Span<'_>| *s.fragment()),
+        blank_lines,
+        lines_of(insertion_rule),
+    )(input)
+}
+
+#[test]
+fn test_polymer() {
+    assert_eq!(
+        parse_all(polymer_instructions, "NNCB\n\n    CH -> B\n    HH -> N"),
+        Ok(("NNCB", vec![(['C', 'H'], 'B'), (['H', 'H'], 'N')]))
+    );
+    let err = parse_all(polymer_instructions, "NNCB\n\n    CH -> B\n    Hh -> N").unwrap_err();
+    assert_eq!((err.line, err.column), (4, 5));
+    assert_eq!(
+        parse_all(
+            bracket_line_list,
+            "[({(<(())[]>\n    [(()[<>])]({[<{<<[]>>("
+        ),
+        Ok(vec!["[({(<(())[]>", "[(()[<>])]({[<{<<[]>>("])
+    );
+}
+
+pub fn edge(input: Span<'_>) -> IResult<Span<'_>, (&str, &str)> {
+    map(
+        separated_pair(alphanumeric1, char('-'), alphanumeric1),
+        |(a, b): (Span<'_>, Span<'_>)| (*a.fragment(), *b.fragment()),
+    )(input)
+}
+
+pub fn edge_list(input: Span<'_>) -> IResult<Span<'_>, Vec<(&str, &str)>> {
+    lines_of(edge)(input)
+}
+
+#[test]
+fn test_edge() {
+    assert_eq!(parse_all(edge, "start-A"), Ok(("start", "A")));
+    assert_eq!(
+        parse_all(edge_list, "dc-end\n    HN-start\n"),
+        Ok(vec![("dc", "end"), ("HN", "start")])
+    );
+}
+
+pub fn snailfish_number(input: Span<'_>) -> IResult<Span<'_>, SfNumber> {
     alt((
         map(signed_decimal, SfNumber::Number),
         delimited(
@@ -282,10 +888,43 @@ pub fn snailfish_number(input: &str) -> IResult<&str, SfNumber> {
         ),
     ))(input)
 }
+
+impl FromStr for SfNumber {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_all(snailfish_number, s)
+    }
+}
+
+/// A snailfish number that is a pair, as every line of the homework has to be. A lone regular
+/// number is a 'Failure' (ErrorKind::Verify) pointing at it, like numbers out of range in
+/// 'integer'.
+pub fn snailfish_pair(input: Span<'_>) -> IResult<Span<'_>, SfNumber> {
+    match snailfish_number(input)? {
+        (rest, n @ SfNumber::Pair(..)) => Ok((rest, n)),
+        (_, SfNumber::Number(_)) => Err(Err::Failure(nom::error::Error::from_error_kind(
+            input,
+            ErrorKind::Verify,
+        ))),
+    }
+}
+
+pub fn snailfish_numbers<R: BufRead>(reader: R) -> Records<R, SfNumber> {
+    Records::lines(reader, snailfish_pair)
+}
+
+pub fn snailfish_number_list(input: Span<'_>) -> IResult<Span<'_>, Vec<SfNumber>> {
+    lines_of(snailfish_pair)(input)
+}
+
 #[test]
 fn snailfish_test() {
-    let (_, mut x) =
-        snailfish_number("[[[[1,3],[5,3]],[[1,3],[8,7]]],[[[4,9],[6,9]],[[8,2],[7,3]]]]").unwrap();
+    let mut x = parse_all(
+        snailfish_number,
+        "[[[[1,3],[5,3]],[[1,3],[8,7]]],[[[4,9],[6,9]],[[8,2],[7,3]]]]",
+    )
+    .unwrap();
     println!("{:?}", x);
     let mut v = x.traverse_left_to_right_vec(0);
     println!("{:?}", v);
@@ -296,62 +935,31 @@ fn snailfish_test() {
     }
     println!("{:?}", v);
 
-    let (_, mut x) = snailfish_number("[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]").unwrap();
-    // loop {
-    //     {
-    //         let mut v = x.traverse_left_to_right_vec(0);
-    //         println!("{:?}", v);
-
-    //         if let Some((explode_pos, _)) = v
-    //             .windows(2)
-    //             .find_position(|x| x[0].1 == x[1].1 && x[0].1 >= 5)
-    //         {
-    //             println!("explode: {:?}", explode_pos);
-    //             // *v[explode_pos].0 = SfNumber::Exploded;
-    //             // *v[explode_pos + 1].0 = SfNumber::Exploded;
-
-    //             if explode_pos > 0 {
-    //                 let n = match (&v[explode_pos - 1].0, &v[explode_pos].0) {
-    //                     (SfNumber::Number(a), SfNumber::Number(b)) => a + b,
-    //                     _ => panic!("bad nodes"),
-    //                 };
-    //                 *v[explode_pos - 1].0 = SfNumber::Number(n);
-    //             }
-    //             if explode_pos < v.len() - 1 {
-    //                 let n = match (&v[explode_pos + 1].0, &v[explode_pos + 2].0) {
-    //                     (SfNumber::Number(a), SfNumber::Number(b)) => a + b,
-    //                     _ => panic!("bad nodes"),
-    //                 };
-    //                 *v[explode_pos + 2].0 = SfNumber::Number(n);
-    //             }
-    //             *v[explode_pos].0 = SfNumber::Exploded;
-    //             *v[explode_pos + 1].0 = SfNumber::Exploded;
-    //             x.prune_exploded();
-    //             continue;
-    //         } else if x.split() {
-    //             println!("split");
-    //             continue;
-    //         }
-    //     }
-    //     break;
-    // }
+    let mut x = parse_all(snailfish_number, "[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]").unwrap();
     x.reduce();
-    println!("v: {:?}", x);
-    // *explode[0].0 = SfNumber::Exploded;
-    // let (a, b) = explode.split_first_mut().unwrap();
-    // let (b,_) -
+    assert_eq!(x.to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+
+    // a lone regular number is a snailfish number, but not a line of the homework
+    assert_eq!(parse_all(snailfish_number, "7"), Ok(SfNumber::Number(7)));
+    let err = parse_all(snailfish_number_list, "[1,2]\n    7\n    [3,4]").unwrap_err();
+    assert_eq!((err.line, err.column, err.kind), (2, 5, ErrorKind::Verify));
+    let records = snailfish_numbers("[1,2]\n7\n".as_bytes()).collect::<Vec<_>>();
+    assert!(records[0].is_ok());
+    match &records[1] {
+        Err(RecordError::Parse(e)) => assert_eq!((e.line, e.kind), (2, ErrorKind::Verify)),
+        x => panic!("expected parse error, got {:?}", x),
+    }
 }
 
-pub fn scanner_head(input: &str) -> IResult<&str, i64> {
-    let (input, num) = delimited(
-        delimited(multispace0, tag("--- scanner"), multispace1),
+pub fn scanner_head(input: Span<'_>) -> IResult<Span<'_>, i64> {
+    delimited(
+        tuple((tag("--- scanner"), space1)),
         signed_decimal,
-        delimited(multispace1, tag("---"), multispace0),
-    )(input)?;
-    Ok((input, num))
+        tuple((space1, tag("---"))),
+    )(input)
 }
 
-pub fn coord3d(input: &str) -> IResult<&str, Vec3> {
+pub fn coord3d(input: Span<'_>) -> IResult<Span<'_>, Vec3> {
     let (input, (x, _, y, _, z)) = tuple((
         signed_decimal,
         char(','),
@@ -363,33 +971,63 @@ pub fn coord3d(input: &str) -> IResult<&str, Vec3> {
     Ok((input, Vec3 { x, y, z }))
 }
 
-pub fn coord3d_list(input: &str) -> IResult<&str, Vec<Vec3>> {
-    separated_list0(multispace0, coord3d)(input)
+pub fn coord3d_list(input: Span<'_>) -> IResult<Span<'_>, Vec<Vec3>> {
+    lines_of(coord3d)(input)
 }
 
-pub fn scanner(input: &str) -> IResult<&str, (i64, Vec<Vec3>)> {
-    let (input, num) = scanner_head(input)?;
-    let (input, coords) = coord3d_list(input)?;
+/// The beacons seen by one scanner (day 19).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScannerReport {
+    pub id: i64,
+    pub beacons: Vec<Vec3>,
+}
 
-    Ok((input, (num, coords)))
+impl fmt::Display for ScannerReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "--- scanner {} ---", self.id)?;
+        for beacon in &self.beacons {
+            write!(f, "\n{}", beacon)?;
+        }
+        Ok(())
+    }
 }
 
-pub fn scanner_list(input: &str) -> IResult<&str, Vec<(i64, Vec<Vec3>)>> {
-    separated_list0(multispace0, scanner)(input)
+impl FromStr for ScannerReport {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_all(scanner, s)
+    }
+}
+
+pub fn scanner(input: Span<'_>) -> IResult<Span<'_>, ScannerReport> {
+    map(
+        separated_pair(scanner_head, line_break, coord3d_list),
+        |(id, beacons)| ScannerReport { id, beacons },
+    )(input)
+}
+
+pub fn scanner_reports<R: BufRead>(reader: R) -> Records<R, ScannerReport> {
+    Records::blocks(reader, scanner)
+}
+
+pub fn scanner_list(input: Span<'_>) -> IResult<Span<'_>, Vec<ScannerReport>> {
+    blocks_separated_by_blank_lines(scanner)(input)
 }
 
 #[test]
 fn test_scanner() {
-    let (input, sl) = scanner_list(
+    let sl = parse_all(
+        scanner_list,
         "--- scanner 0 ---
     404,-588,-901
     528,-643,409
-    
+
     --- scanner 1 ---
     686,422,578
     605,423,415
     515,917,-361
-    
+
     --- scanner 2 ---
     649,640,665
     682,-795,504
@@ -400,15 +1038,15 @@ fn test_scanner() {
     .unwrap();
 
     assert_eq!(sl.len(), 3);
-    assert_eq!(sl[0].0, 0);
-    assert_eq!(sl[1].0, 1);
-    assert_eq!(sl[2].0, 2);
+    assert_eq!(sl[0].id, 0);
+    assert_eq!(sl[1].id, 1);
+    assert_eq!(sl[2].id, 2);
 
-    assert_eq!(sl[0].1.len(), 2);
-    assert_eq!(sl[1].1.len(), 3);
-    assert_eq!(sl[2].1.len(), 4);
+    assert_eq!(sl[0].beacons.len(), 2);
+    assert_eq!(sl[1].beacons.len(), 3);
+    assert_eq!(sl[2].beacons.len(), 4);
     assert_eq!(
-        sl[0].1[0],
+        sl[0].beacons[0],
         Vec3 {
             x: 404,
             y: -588,
@@ -416,7 +1054,7 @@ fn test_scanner() {
         }
     );
     assert_eq!(
-        sl[1].1[1],
+        sl[1].beacons[1],
         Vec3 {
             x: 605,
             y: 423,
@@ -424,7 +1062,7 @@ fn test_scanner() {
         }
     );
     assert_eq!(
-        sl[2].1[2],
+        sl[2].beacons[2],
         Vec3 {
             x: -784,
             y: 533,
@@ -433,59 +1071,283 @@ fn test_scanner() {
     );
 }
 
-pub fn range(input: &str) -> IResult<&str, RangeInclusive<i64>> {
+pub fn range(input: Span<'_>) -> IResult<Span<'_>, RangeInclusive<i64>> {
     map(
         separated_pair(signed_decimal, tag(".."), signed_decimal),
         |(l, r)| l..=r,
     )(input)
 }
-pub fn named_range(input: &str) -> IResult<&str, (&str, RangeInclusive<i64>)> {
-    separated_pair(alpha1, tag("="), range)(input)
+pub fn named_range(input: Span<'_>) -> IResult<Span<'_>, (&str, RangeInclusive<i64>)> {
+    map(separated_pair(alpha1, tag("="), range), |(name, r)| {
+        (*name.fragment(), r)
+    })(input)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OnOff {
+    On,
+    Off,
 }
 
-type RebootStep = (
-    bool,
-    RangeInclusive<i64>,
-    RangeInclusive<i64>,
-    RangeInclusive<i64>,
-);
+impl fmt::Display for OnOff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OnOff::On => write!(f, "on"),
+            OnOff::Off => write!(f, "off"),
+        }
+    }
+}
 
-pub fn reboot_step(input: &str) -> IResult<&str, RebootStep> {
-    let (input, (on_off, ranges)) = separated_pair(
-        alt((tag("on"), tag("off"))),
-        space1,
-        separated_list1(tag(","), named_range),
-    )(input)?;
+pub const XYZ: [&str; 3] = ["x", "y", "z"];
 
-    let on_off = match on_off {
-        "on" => true,
-        "off" => false,
-        _ => panic!("unhandled on_off"),
-    };
+/// Switch all cubes in the given ranges on or off (day 22). 'ranges' are in the order of 'axes',
+/// whatever the order in the input was.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RebootStep<const N: usize = 3> {
+    pub state: OnOff,
+    pub axes: [&'static str; N],
+    pub ranges: [RangeInclusive<i64>; N],
+}
 
-    assert_eq!(ranges[0].0, "x");
-    assert_eq!(ranges[1].0, "y");
-    assert_eq!(ranges[2].0, "z");
-
-    Ok((
-        input,
-        (
-            on_off,
-            ranges[0].1.clone(),
-            ranges[1].1.clone(),
-            ranges[2].1.clone(),
-        ),
-    ))
+impl RebootStep<3> {
+    pub fn cube(&self) -> Cube {
+        self.boxn()
+    }
+}
+
+impl<const N: usize> RebootStep<N> {
+    pub fn boxn(&self) -> BoxN<N> {
+        BoxN::from(self.ranges.clone())
+    }
+}
+
+impl<const N: usize> fmt::Display for RebootStep<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} ", self.state)?;
+        for (i, (axis, range)) in self.axes.iter().zip(self.ranges.iter()).enumerate() {
+            if i > 0 {
+                write!(f, ",")?;
+            }
+            write!(f, "{}={}..{}", axis, range.start(), range.end())?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for RebootStep {
+    type Err = ParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse_all(reboot_step, s)
+    }
+}
+
+/// Reboot step with one named range for each of 'axes', in any order. Unknown or repeated axes
+/// are a 'Failure' at the offending range, missing ones at the start of the step.
+pub fn reboot_step_with_axes<'a, const N: usize>(
+    axes: [&'static str; N],
+) -> impl FnMut(Span<'a>) -> IResult<Span<'a>, RebootStep<N>> {
+    move |input| {
+        let (rest, (state, named)) = separated_pair(
+            alt((value(OnOff::On, tag("on")), value(OnOff::Off, tag("off")))),
+            space1,
+            separated_list1(tag(","), tuple((position, named_range))),
+        )(input)?;
+
+        let mut ranges: [Option<RangeInclusive<i64>>; N] = std::array::from_fn(|_| None);
+        for (pos, (name, range)) in named {
+            match axes.iter().position(|axis| *axis == name) {
+                Some(i) if ranges[i].is_none() => ranges[i] = Some(range),
+                _ => {
+                    return Err(Err::Failure(nom::error::Error::from_error_kind(
+                        pos,
+                        ErrorKind::Verify,
+                    )))
+                }
+            }
+        }
+        if ranges.iter().any(Option::is_none) {
+            return Err(Err::Failure(nom::error::Error::from_error_kind(
+                input,
+                ErrorKind::Verify,
+            )));
+        }
+        Ok((
+            rest,
+            RebootStep {
+                state,
+                axes,
+                ranges: ranges.map(Option::unwrap),
+            },
+        ))
+    }
+}
+
+pub fn reboot_step(input: Span<'_>) -> IResult<Span<'_>, RebootStep> {
+    reboot_step_with_axes(XYZ)(input)
+}
+
+pub fn reboot_steps<R: BufRead>(reader: R) -> Records<R, RebootStep> {
+    Records::lines(reader, reboot_step)
+}
+
+pub fn reboot_step_list(input: Span<'_>) -> IResult<Span<'_>, Vec<RebootStep>> {
+    lines_of(reboot_step)(input)
+}
+
+/// An ALU instruction like "add x -3": the operation and its operands, each a register name or a
+/// number.
+pub fn alu_instruction(input: Span<'_>) -> IResult<Span<'_>, (&str, Vec<&str>)> {
+    let operand = alt((alpha1, recognize(tuple((opt(char('-')), decimal)))));
+    map(
+        tuple((alpha1, many1(preceded(space1, operand)))),
+        |(op, operands): (Span<'_>, Vec<Span<'_>>)| {
+            (
+                *op.fragment(),
+                operands.iter().map(|s| *s.fragment()).collect(),
+            )
+        },
+    )(input)
+}
+
+pub fn alu_program(input: Span<'_>) -> IResult<Span<'_>, Vec<(&str, Vec<&str>)>> {
+    lines_of(alu_instruction)(input)
+}
+
+/// A row of the sea floor of day 25: '>' and 'v' for sea cucumbers, '.' for empty places.
+pub fn sea_cucumber_row(input: Span<'_>) -> IResult<Span<'_>, Vec<char>> {
+    many1(one_of(">v."))(input)
+}
+
+pub fn sea_cucumber_field(input: Span<'_>) -> IResult<Span<'_>, Vec<Vec<char>>> {
+    lines_of(sea_cucumber_row)(input)
 }
 
-pub fn reboot_step_list(input: &str) -> IResult<&str, Vec<RebootStep>> {
-    separated_list1(multispace1, reboot_step)(input)
+#[test]
+fn test_alu_and_sea_cucumbers() {
+    assert_eq!(
+        parse_all(alu_program, "inp w\n    add z -12\n    mul x y"),
+        Ok(vec![
+            ("inp", vec!["w"]),
+            ("add", vec!["z", "-12"]),
+            ("mul", vec!["x", "y"])
+        ])
+    );
+    assert!(parse_all(alu_program, "inp w\n    add z 1 2x").is_err());
+    assert_eq!(
+        parse_all(sea_cucumber_field, "v..>\n    .>v."),
+        Ok(vec![vec!['v', '.', '.', '>'], vec!['.', '>', 'v', '.']])
+    );
 }
 
 #[test]
 fn test_reboot_step() {
     assert_eq!(
-        reboot_step("on x=-22..26,y=-27..20,z=-29..19").unwrap(),
-        ("", (true, -22..=26, -27..=20, -29..=19))
+        parse_all(reboot_step, "on x=-22..26,y=-27..20,z=-29..19"),
+        Ok(RebootStep {
+            state: OnOff::On,
+            axes: XYZ,
+            ranges: [-22..=26, -27..=20, -29..=19]
+        })
+    );
+    assert_eq!(
+        parse_all(reboot_step, "off z=5..6,x=1..2,y=3..4").map(|step| step.ranges),
+        Ok([1..=2, 3..=4, 5..=6])
+    );
+    let err = parse_all(
+        reboot_step_list,
+        "on x=-22..26,y=-27..20,z=-29..19\n    off x=1..2,w=1..2,z=1..2",
+    )
+    .unwrap_err();
+    assert_eq!((err.line, err.column, err.kind), (2, 16, ErrorKind::Verify));
+    let err = parse_all(reboot_step, "on x=1..2,y=3..4,x=1..2").unwrap_err();
+    assert_eq!((err.column, err.kind), (18, ErrorKind::Verify));
+    let err = parse_all(reboot_step, "on x=1..2,y=3..4").unwrap_err();
+    assert_eq!((err.column, err.kind), (1, ErrorKind::Verify));
+
+    let step = parse_all(reboot_step_with_axes(["x", "y"]), "off y=3..4,x=1..2").unwrap();
+    assert_eq!(step.to_string(), "off x=1..2,y=3..4");
+    let step = parse_all(
+        reboot_step_with_axes(["x", "y", "z", "w"]),
+        "on w=0..1,x=1..2,y=3..4,z=5..6",
     )
+    .unwrap();
+    assert_eq!(step.ranges[3], 0..=1);
+}
+
+#[test]
+fn test_records() {
+    let input = "forward 5
+    down 5
+
+    sideways 8
+    up 3
+    ";
+    let commands = submarine_commands(input.as_bytes()).collect::<Vec<_>>();
+    assert_eq!(commands.len(), 4);
+    assert_eq!(commands[1].as_ref().unwrap(), &SubmarineCommand::Down(5));
+    match &commands[2] {
+        Err(RecordError::Parse(e)) => assert_eq!((e.line, e.column), (4, 5)),
+        x => panic!("expected parse error, got {:?}", x),
+    }
+    assert_eq!(commands[3].as_ref().unwrap(), &SubmarineCommand::Up(3));
+
+    let input = "
+    --- scanner 0 ---
+    404,-588,-901
+    528,-643,409
+
+
+    --- scanner 1 ---
+    686,422,578
+    605,423,x15
+    ";
+    let reports = scanner_reports(input.as_bytes()).collect::<Vec<_>>();
+    assert_eq!(reports.len(), 2);
+    assert_eq!(reports[0].as_ref().unwrap().beacons.len(), 2);
+    match &reports[1] {
+        Err(RecordError::Parse(e)) => assert_eq!(e.line, 9),
+        x => panic!("expected parse error, got {:?}", x),
+    }
+}
+
+#[cfg(test)]
+fn assert_round_trip<T>(input: &str)
+where
+    T: FromStr<Err = ParseError> + fmt::Display + PartialEq + fmt::Debug,
+{
+    let x: T = input.parse().unwrap();
+    assert_eq!(x.to_string(), input);
+    assert_eq!(x.to_string().parse(), Ok(x));
+}
+
+#[test]
+fn test_round_trip() {
+    assert_round_trip::<SubmarineCommand>("forward 5");
+    assert_round_trip::<SubmarineCommand>("down -3");
+    assert_round_trip::<FoldInstruction>("fold along x=655");
+    assert_round_trip::<SfNumber>("[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]");
+    assert_round_trip::<BingoBoard>(
+        "22 13 17 11  0\n 8  2 23  4 24\n21  9 14 16  7\n 6 10  3 18  5\n 1 12 20 15 19",
+    );
+    assert_round_trip::<RebootStep>("off x=-54112..-39298,y=-85059..-49293,z=-27449..7877");
+    assert_round_trip::<ScannerReport>("--- scanner 1 ---\n686,422,578\n-336,658,858");
+
+    // the canonical form doesn't depend on the layout of the input
+    let board: BingoBoard = "
+        22 13 17 11  0
+         8  2 23  4 24
+        21  9 14 16  7
+         6 10  3 18  5
+         1 12 20 15 19"
+        .parse()
+        .unwrap();
+    assert_eq!(board.to_string().parse(), Ok(board.clone()));
+
+    // marked numbers aren't part of the text form, and don't affect equality either
+    let mut played = board.clone();
+    assert!(!played.apply(22));
+    assert!(!played.apply(8));
+    assert_eq!(played.to_string().parse(), Ok(played.clone()));
+    assert_eq!(played, board);
 }
@@ -0,0 +1,218 @@
+//! Sets of integers kept as sorted, disjoint inclusive ranges, with ranges that touch merged.
+
+use std::ops::RangeInclusive;
+
+use crate::{overlap_type, Overlap};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct RangeSet {
+    ranges: Vec<RangeInclusive<i64>>,
+}
+
+impl FromIterator<RangeInclusive<i64>> for RangeSet {
+    fn from_iter<I: IntoIterator<Item = RangeInclusive<i64>>>(iter: I) -> Self {
+        let mut set = RangeSet::default();
+        for range in iter {
+            set.insert(range);
+        }
+        set
+    }
+}
+
+impl RangeSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The ranges in increasing order. Neither overlap nor touch.
+    pub fn ranges(&self) -> &[RangeInclusive<i64>] {
+        &self.ranges
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// How many integers are in the set.
+    pub fn len(&self) -> u128 {
+        self.ranges
+            .iter()
+            .map(|r| (*r.end() as i128 - *r.start() as i128 + 1) as u128)
+            .sum()
+    }
+
+    pub fn insert(&mut self, range: RangeInclusive<i64>) {
+        if range.is_empty() {
+            return;
+        }
+        let (mut start, mut end) = range.into_inner();
+        // the stored ranges that overlap or touch the new one are ranges[first..last]
+        let first = self
+            .ranges
+            .partition_point(|r| r.end().saturating_add(1) < start);
+        let last = self
+            .ranges
+            .partition_point(|r| r.start().saturating_sub(1) <= end);
+        if first < last {
+            start = start.min(*self.ranges[first].start());
+            end = end.max(*self.ranges[last - 1].end());
+        }
+        self.ranges.splice(first..last, [start..=end]);
+    }
+
+    pub fn remove(&mut self, range: RangeInclusive<i64>) {
+        if range.is_empty() {
+            return;
+        }
+        let (start, end) = (*range.start(), *range.end());
+        let first = self.ranges.partition_point(|r| *r.end() < start);
+        let last = self.ranges.partition_point(|r| *r.start() <= end);
+
+        let mut kept = Vec::new();
+        for r in &self.ranges[first..last] {
+            let before = start
+                .checked_sub(1)
+                .map(|before| *r.start()..=before)
+                .filter(|b| !b.is_empty());
+            let after = end
+                .checked_add(1)
+                .map(|after| after..=*r.end())
+                .filter(|a| !a.is_empty());
+            match overlap_type(r.clone(), range.clone()) {
+                Overlap::Equals | Overlap::Contained => {}
+                Overlap::Contains => kept.extend(before.into_iter().chain(after)),
+                Overlap::Start => kept.extend(before),
+                Overlap::End => kept.extend(after),
+                Overlap::None => kept.push(r.clone()),
+            }
+        }
+        self.ranges.splice(first..last, kept);
+    }
+
+    pub fn contains(&self, n: i64) -> bool {
+        self.containing(n).is_some()
+    }
+
+    /// The stored range holding 'n'.
+    pub fn containing(&self, n: i64) -> Option<&RangeInclusive<i64>> {
+        let i = self.ranges.partition_point(|r| *r.end() < n);
+        self.ranges.get(i).filter(|r| r.contains(&n))
+    }
+
+    /// Whether all of 'range' is in the set.
+    pub fn contains_range(&self, range: &RangeInclusive<i64>) -> bool {
+        range.is_empty()
+            || self.containing(*range.start()).is_some_and(|r| {
+                matches!(
+                    overlap_type(r.clone(), range.clone()),
+                    Overlap::Equals | Overlap::Contains
+                )
+            })
+    }
+
+    /// Whether any of 'range' is in the set.
+    pub fn overlaps(&self, range: &RangeInclusive<i64>) -> bool {
+        let i = self.ranges.partition_point(|r| r.end() < range.start());
+        !range.is_empty()
+            && self
+                .ranges
+                .get(i)
+                .is_some_and(|r| overlap_type(r.clone(), range.clone()) != Overlap::None)
+    }
+
+    pub fn union(&self, other: &RangeSet) -> RangeSet {
+        let mut union = self.clone();
+        for range in &other.ranges {
+            union.insert(range.clone());
+        }
+        union
+    }
+
+    pub fn intersection(&self, other: &RangeSet) -> RangeSet {
+        let (mut i, mut j) = (0, 0);
+        let mut ranges = Vec::new();
+        while let (Some(a), Some(b)) = (self.ranges.get(i), other.ranges.get(j)) {
+            let common = *a.start().max(b.start())..=*a.end().min(b.end());
+            if !common.is_empty() {
+                ranges.push(common);
+            }
+            if a.end() < b.end() {
+                i += 1;
+            } else {
+                j += 1;
+            }
+        }
+        RangeSet { ranges }
+    }
+
+    pub fn difference(&self, other: &RangeSet) -> RangeSet {
+        let mut difference = self.clone();
+        for range in &other.ranges {
+            difference.remove(range.clone());
+        }
+        difference
+    }
+
+    /// Everything in 'within' that is not in the set.
+    pub fn complement(&self, within: RangeInclusive<i64>) -> RangeSet {
+        RangeSet::from_iter([within]).difference(self)
+    }
+
+    /// The ranges between the stored ones, in increasing order.
+    pub fn gaps(&self) -> impl Iterator<Item = RangeInclusive<i64>> + '_ {
+        self.ranges
+            .windows(2)
+            .map(|w| *w[0].end() + 1..=*w[1].start() - 1)
+    }
+}
+
+#[test]
+fn test_range_set() {
+    let mut set = RangeSet::new();
+    set.insert(10..=20);
+    set.insert(30..=40);
+    set.insert(21..=25);
+    assert_eq!(set.ranges(), [10..=25, 30..=40]);
+    set.insert(0..=2);
+    set.insert(24..=29);
+    assert_eq!(set.ranges(), [0..=2, 10..=40]);
+    assert_eq!(set.len(), 3 + 31);
+    assert_eq!(set.gaps().collect::<Vec<_>>(), [3..=9]);
+
+    set.remove(15..=19);
+    set.remove(-5..=0);
+    set.remove(40..=50);
+    assert_eq!(set.ranges(), [1..=2, 10..=14, 20..=39]);
+    set.remove(1..=14);
+    assert_eq!(set.ranges(), [20..=39]);
+    set.remove(25..=25);
+    assert_eq!(set.ranges(), [20..=24, 26..=39]);
+
+    assert!(set.contains(20));
+    assert!(!set.contains(25));
+    assert_eq!(set.containing(30), Some(&(26..=39)));
+    assert!(set.contains_range(&(26..=39)));
+    assert!(set.contains_range(&(27..=30)));
+    assert!(!set.contains_range(&(24..=26)));
+    assert!(set.overlaps(&(25..=26)));
+    assert!(!set.overlaps(&(40..=100)));
+    assert!(!set.overlaps(&(25..=25)));
+
+    let other = RangeSet::from_iter([0..=21, 30..=31, 38..=45]);
+    assert_eq!(set.union(&other).ranges(), [0..=24, 26..=45]);
+    assert_eq!(
+        set.intersection(&other).ranges(),
+        [20..=21, 30..=31, 38..=39]
+    );
+    assert_eq!(set.difference(&other).ranges(), [22..=24, 26..=29, 32..=37]);
+    assert_eq!(set.complement(0..=50).ranges(), [0..=19, 25..=25, 40..=50]);
+
+    let all = RangeSet::from_iter([i64::MIN..=i64::MAX]);
+    assert_eq!(all.len(), 1 << 64);
+    let mut holes = all.clone();
+    holes.remove(i64::MIN..=i64::MIN);
+    holes.remove(0..=0);
+    holes.remove(i64::MAX..=i64::MAX);
+    assert_eq!(holes.ranges(), [i64::MIN + 1..=-1, 1..=i64::MAX - 1]);
+    assert_eq!(holes.complement(i64::MIN..=i64::MAX).len(), 3);
+}
@@ -0,0 +1,684 @@
+use std::{
+    cmp::Reverse,
+    collections::{hash_map::Entry, BinaryHeap, HashMap},
+    hash::Hash,
+    ops::Add,
+};
+
+use indexmap::{map::Entry as IndexEntry, IndexMap};
+
+/// Counters collected while running a search.
+///
+/// - 'expanded' is the number of nodes taken from the open list whose successors were generated.
+/// - 'queued' is the number of entries pushed onto the open list (a node is pushed again every
+///   time a cheaper way to reach it is found).
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct SearchStats {
+    pub expanded: usize,
+    pub queued: usize,
+}
+
+const NO_PARENT: usize = usize::MAX;
+
+/// Bookkeeping shared by all best-first searches: every node seen so far, with the index of its
+/// parent and the cheapest known cost to reach it. Each node is stored once, nodes are referred to
+/// by their index in insertion order.
+struct Visited<N, C> {
+    nodes: IndexMap<N, (usize, C)>,
+}
+
+impl<N, C> Visited<N, C>
+where
+    N: Eq + Hash + Clone,
+    C: Copy,
+{
+    fn new(start: &N, zero: C) -> Self {
+        Visited {
+            nodes: IndexMap::from([(start.clone(), (NO_PARENT, zero))]),
+        }
+    }
+
+    fn node(&self, i: usize) -> &N {
+        self.nodes.get_index(i).unwrap().0
+    }
+
+    fn cost(&self, i: usize) -> C {
+        self.nodes[i].1
+    }
+
+    fn path(&self, mut i: usize) -> Vec<N> {
+        let mut path = Vec::new();
+        while i != NO_PARENT {
+            path.push(self.node(i).clone());
+            i = self.nodes[i].0;
+        }
+        path.reverse();
+        path
+    }
+}
+
+fn best_first<N, C, FN, IN, FH, FS>(
+    start: &N,
+    mut successors: FN,
+    mut heuristic: FH,
+    mut success: FS,
+    stats: &mut SearchStats,
+) -> (Visited<N, C>, Option<usize>)
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FS: FnMut(&N) -> bool,
+{
+    let zero = C::default();
+    let mut visited = Visited::new(start, zero);
+    let mut open = BinaryHeap::new();
+    open.push(Reverse((heuristic(start), zero, 0)));
+    stats.queued += 1;
+
+    while let Some(Reverse((_, cost, i))) = open.pop() {
+        if cost > visited.cost(i) {
+            // stale entry, a cheaper way to this node was found after it was queued
+            continue;
+        }
+        let node = visited.node(i).clone();
+        if success(&node) {
+            return (visited, Some(i));
+        }
+        stats.expanded += 1;
+        for (succ, move_cost) in successors(&node) {
+            let new_cost = cost + move_cost;
+            let j = match visited.nodes.entry(succ) {
+                IndexEntry::Vacant(e) => {
+                    let j = e.index();
+                    e.insert((i, new_cost));
+                    j
+                }
+                IndexEntry::Occupied(mut e) => {
+                    if e.get().1 <= new_cost {
+                        continue;
+                    }
+                    *e.get_mut() = (i, new_cost);
+                    e.index()
+                }
+            };
+            let estimate = new_cost + heuristic(visited.node(j));
+            open.push(Reverse((estimate, new_cost, j)));
+            stats.queued += 1;
+        }
+    }
+    (visited, None)
+}
+
+/// Find the cheapest path from 'start' to a node for which 'success' returns 'true'.
+///
+/// - 'successors' returns the reachable nodes for a given node, together with the cost of moving
+///   there. Costs must not be negative.
+///
+/// Returns the path (including 'start' and the goal) and its total cost.
+pub fn dijkstra<N, C, FN, IN, FS>(start: &N, successors: FN, success: FS) -> Option<(Vec<N>, C)>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FS: FnMut(&N) -> bool,
+{
+    astar(start, successors, |_| C::default(), success)
+}
+
+/// Like 'dijkstra', but guided by 'heuristic', which estimates the remaining cost from a node to
+/// the goal. The result is only guaranteed to be optimal if the estimate never exceeds the actual
+/// remaining cost.
+pub fn astar<N, C, FN, IN, FH, FS>(
+    start: &N,
+    successors: FN,
+    heuristic: FH,
+    success: FS,
+) -> Option<(Vec<N>, C)>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FS: FnMut(&N) -> bool,
+{
+    astar_with_stats(start, successors, heuristic, success).0
+}
+
+/// 'astar' that additionally reports how many nodes were expanded and queued, e.g. to compare
+/// heuristics on the same problem.
+pub fn astar_with_stats<N, C, FN, IN, FH, FS>(
+    start: &N,
+    successors: FN,
+    heuristic: FH,
+    success: FS,
+) -> (Option<(Vec<N>, C)>, SearchStats)
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FS: FnMut(&N) -> bool,
+{
+    let mut stats = SearchStats::default();
+    let (visited, goal) = best_first(start, successors, heuristic, success, &mut stats);
+    let res = goal.map(|i| (visited.path(i), visited.cost(i)));
+    (res, stats)
+}
+
+/// Map from each reachable node to its predecessor on a cheapest path ('None' for the start node)
+/// and the cost of that path.
+pub type CameFrom<N, C> = HashMap<N, (Option<N>, C)>;
+
+/// Explore everything reachable from 'start' in order of increasing cost.
+///
+/// Returns the full came-from map, which holds the cheapest cost for every reachable node and can
+/// be turned into paths with 'reconstruct_path'.
+pub fn uniform_cost_search<N, C, FN, IN>(start: &N, successors: FN) -> (CameFrom<N, C>, SearchStats)
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+{
+    let mut stats = SearchStats::default();
+    let (visited, _) = best_first(start, successors, |_| C::default(), |_| false, &mut stats);
+    let came_from = visited
+        .nodes
+        .iter()
+        .map(|(node, (parent, cost))| {
+            let parent = (*parent != NO_PARENT).then(|| visited.node(*parent).clone());
+            (node.clone(), (parent, *cost))
+        })
+        .collect();
+    (came_from, stats)
+}
+
+/// Follow the predecessors in 'came_from' back from 'goal'. Returns the path from the start node
+/// to 'goal', or 'None' if 'goal' was not reached.
+pub fn reconstruct_path<N, C>(came_from: &CameFrom<N, C>, goal: &N) -> Option<Vec<N>>
+where
+    N: Eq + Hash + Clone,
+{
+    let mut path = vec![goal.clone()];
+    let mut cur = came_from.get(goal)?;
+    while let (Some(parent), _) = cur {
+        path.push(parent.clone());
+        cur = came_from.get(parent)?;
+    }
+    path.reverse();
+    Some(path)
+}
+
+/// Find the cheapest path from 'start' to a node for which 'success' returns 'true' using
+/// iterative-deepening A*.
+///
+/// Takes the same arguments as 'astar', but only keeps the current path in memory instead of every
+/// visited node, at the price of expanding nodes repeatedly. Nodes already on the current path are
+/// not revisited, so cyclic state spaces are fine.
+pub fn ida_star<N, C, FN, IN, FH, FS>(
+    start: &N,
+    mut successors: FN,
+    mut heuristic: FH,
+    mut success: FS,
+) -> Option<(Vec<N>, C)>
+where
+    N: Eq + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FH: FnMut(&N) -> C,
+    FS: FnMut(&N) -> bool,
+{
+    enum Bound<C> {
+        Found(C),
+        Exceeded(C),
+        Exhausted,
+    }
+
+    fn search<N, C, FN, IN, FH, FS>(
+        path: &mut Vec<N>,
+        cost: C,
+        bound: C,
+        successors: &mut FN,
+        heuristic: &mut FH,
+        success: &mut FS,
+    ) -> Bound<C>
+    where
+        N: Eq + Clone,
+        C: Copy + Ord + Default + Add<Output = C>,
+        FN: FnMut(&N) -> IN,
+        IN: IntoIterator<Item = (N, C)>,
+        FH: FnMut(&N) -> C,
+        FS: FnMut(&N) -> bool,
+    {
+        let node = path.last().unwrap().clone();
+        let estimate = cost + heuristic(&node);
+        if estimate > bound {
+            return Bound::Exceeded(estimate);
+        }
+        if success(&node) {
+            return Bound::Found(cost);
+        }
+        let mut next_bound = Bound::Exhausted;
+        for (succ, move_cost) in successors(&node) {
+            if path.contains(&succ) {
+                continue;
+            }
+            path.push(succ);
+            match search(
+                path,
+                cost + move_cost,
+                bound,
+                successors,
+                heuristic,
+                success,
+            ) {
+                Bound::Found(c) => return Bound::Found(c),
+                Bound::Exceeded(e) => match next_bound {
+                    Bound::Exceeded(n) if n <= e => (),
+                    _ => next_bound = Bound::Exceeded(e),
+                },
+                Bound::Exhausted => (),
+            }
+            path.pop();
+        }
+        next_bound
+    }
+
+    let mut bound = heuristic(start);
+    let mut path = vec![start.clone()];
+    loop {
+        match search(
+            &mut path,
+            C::default(),
+            bound,
+            &mut successors,
+            &mut heuristic,
+            &mut success,
+        ) {
+            Bound::Found(cost) => return Some((path, cost)),
+            Bound::Exceeded(next) => bound = next,
+            Bound::Exhausted => return None,
+        }
+    }
+}
+
+/// Find a shortest path (by number of steps) from 'start' to 'goal' by searching from both ends
+/// at once.
+///
+/// - 'successors' returns the nodes reachable from a given node.
+/// - 'predecessors' returns the nodes from which a given node can be reached. For undirected
+///   state spaces this is the same function as 'successors'.
+///
+/// Both searches only need to go half as deep as a plain BFS, which keeps the number of stored
+/// nodes much smaller on state spaces with a large branching factor. Unlike the other searches
+/// this needs one concrete goal instead of a 'success' predicate, and a way to step backwards,
+/// because the second search starts at the goal. See 'bidirectional_dijkstra' for weighted moves.
+pub fn bidirectional_bfs<N, FN, IN, FP, IP>(
+    start: &N,
+    goal: &N,
+    mut successors: FN,
+    mut predecessors: FP,
+) -> Option<Vec<N>>
+where
+    N: Eq + Hash + Clone,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = N>,
+    FP: FnMut(&N) -> IP,
+    IP: IntoIterator<Item = N>,
+{
+    // 'parents' maps every node seen from one side to the node it was first reached from
+    fn expand_layer<N, FE, IE>(
+        frontier: &mut Vec<N>,
+        parents: &mut HashMap<N, Option<N>>,
+        expand: &mut FE,
+    ) where
+        N: Eq + Hash + Clone,
+        FE: FnMut(&N) -> IE,
+        IE: IntoIterator<Item = N>,
+    {
+        let mut next = Vec::new();
+        for node in frontier.drain(..) {
+            for n in expand(&node) {
+                if let Entry::Vacant(e) = parents.entry(n.clone()) {
+                    e.insert(Some(node.clone()));
+                    next.push(n);
+                }
+            }
+        }
+        *frontier = next;
+    }
+
+    fn walk<N: Eq + Hash + Clone>(parents: &HashMap<N, Option<N>>, from: &N) -> Vec<N> {
+        let mut path = vec![from.clone()];
+        while let Some(Some(p)) = parents.get(path.last().unwrap()) {
+            path.push(p.clone());
+        }
+        path
+    }
+
+    if start == goal {
+        return Some(vec![start.clone()]);
+    }
+    let mut forward = HashMap::from([(start.clone(), None)]);
+    let mut backward = HashMap::from([(goal.clone(), None)]);
+    let mut forward_frontier = vec![start.clone()];
+    let mut backward_frontier = vec![goal.clone()];
+
+    while !forward_frontier.is_empty() && !backward_frontier.is_empty() {
+        // always grow the smaller side by one full layer
+        let layer = if forward_frontier.len() <= backward_frontier.len() {
+            expand_layer(&mut forward_frontier, &mut forward, &mut successors);
+            &forward_frontier
+        } else {
+            expand_layer(&mut backward_frontier, &mut backward, &mut predecessors);
+            &backward_frontier
+        };
+        // All nodes of the new layer are equally far from their own side, but the nodes seen by
+        // the other side can come from different layers, so pick the meeting point with the
+        // shortest combined path.
+        let meet = layer
+            .iter()
+            .filter(|n| forward.contains_key(*n) && backward.contains_key(*n))
+            .min_by_key(|n| walk(&forward, n).len() + walk(&backward, n).len());
+        if let Some(meet) = meet {
+            let mut path = walk(&forward, meet);
+            path.reverse();
+            path.extend(walk(&backward, meet).into_iter().skip(1));
+            return Some(path);
+        }
+    }
+    None
+}
+
+/// Find the cheapest path from 'start' to 'goal' by running Dijkstra from both ends at once.
+///
+/// - 'successors' returns the reachable nodes for a given node, together with the cost of moving
+///   there. Costs must not be negative.
+/// - 'predecessors' returns the nodes from which a given node can be reached, together with the
+///   cost of that move.
+///
+/// Like 'bidirectional_bfs' this needs a concrete goal and the moves leading into a node, so the
+/// day 23 burrow (which only has a forward move generator) would need a 'predecessors' function
+/// first. In return each side only has to cover about half the cost, which stores far fewer
+/// nodes than 'dijkstra' when the state space grows quickly with the cost.
+///
+/// Returns the path (including 'start' and 'goal') and its total cost.
+pub fn bidirectional_dijkstra<N, C, FN, IN, FP, IP>(
+    start: &N,
+    goal: &N,
+    mut successors: FN,
+    mut predecessors: FP,
+) -> Option<(Vec<N>, C)>
+where
+    N: Eq + Hash + Clone,
+    C: Copy + Ord + Default + Add<Output = C>,
+    FN: FnMut(&N) -> IN,
+    IN: IntoIterator<Item = (N, C)>,
+    FP: FnMut(&N) -> IP,
+    IP: IntoIterator<Item = (N, C)>,
+{
+    struct Side<N, C> {
+        visited: Visited<N, C>,
+        open: BinaryHeap<Reverse<(C, usize)>>,
+    }
+
+    impl<N, C> Side<N, C>
+    where
+        N: Eq + Hash + Clone,
+        C: Copy + Ord + Default + Add<Output = C>,
+    {
+        fn new(start: &N) -> Self {
+            Side {
+                visited: Visited::new(start, C::default()),
+                open: BinaryHeap::from([Reverse((C::default(), 0))]),
+            }
+        }
+
+        /// Cost of the cheapest open node, skipping stale entries.
+        fn top(&mut self) -> Option<C> {
+            while let Some(Reverse((cost, i))) = self.open.peek() {
+                if *cost <= self.visited.cost(*i) {
+                    return Some(*cost);
+                }
+                self.open.pop();
+            }
+            None
+        }
+
+        /// Expand the cheapest open node. Returns the cheapest path found through a node the
+        /// other side has seen as (cost, index on this side, index on the other side).
+        fn expand<FE, IE>(&mut self, other: &Self, expand: &mut FE) -> Option<(C, usize, usize)>
+        where
+            FE: FnMut(&N) -> IE,
+            IE: IntoIterator<Item = (N, C)>,
+        {
+            let Reverse((cost, i)) = self.open.pop()?;
+            let node = self.visited.node(i).clone();
+            let mut meet: Option<(C, usize, usize)> = None;
+            for (succ, move_cost) in expand(&node) {
+                let new_cost = cost + move_cost;
+                let j = match self.visited.nodes.entry(succ) {
+                    IndexEntry::Vacant(e) => {
+                        let j = e.index();
+                        e.insert((i, new_cost));
+                        j
+                    }
+                    IndexEntry::Occupied(mut e) => {
+                        if e.get().1 <= new_cost {
+                            continue;
+                        }
+                        *e.get_mut() = (i, new_cost);
+                        e.index()
+                    }
+                };
+                self.open.push(Reverse((new_cost, j)));
+                if let Some(k) = other.visited.nodes.get_index_of(self.visited.node(j)) {
+                    let total = new_cost + other.visited.cost(k);
+                    if meet.is_none_or(|(c, _, _)| total < c) {
+                        meet = Some((total, j, k));
+                    }
+                }
+            }
+            meet
+        }
+    }
+
+    let mut forward = Side::new(start);
+    let mut backward = Side::new(goal);
+    // cheapest known path as (cost, index in forward, index in backward)
+    let mut best = (start == goal).then_some((C::default(), 0, 0));
+    // Stop once the cheapest open nodes of both sides together can't beat the best path, any
+    // cheaper path would have to go through an open node of each side.
+    while let (Some(f), Some(b)) = (forward.top(), backward.top()) {
+        if best.is_some_and(|(c, _, _)| f + b >= c) {
+            break;
+        }
+        let meet = if f <= b {
+            forward.expand(&backward, &mut successors)
+        } else {
+            backward
+                .expand(&forward, &mut predecessors)
+                .map(|(c, bi, fi)| (c, fi, bi))
+        };
+        if let Some(m) = meet {
+            if best.is_none_or(|b| m.0 < b.0) {
+                best = Some(m);
+            }
+        }
+    }
+
+    let (_, fi, bi) = best?;
+    // both halves may have become cheaper since they met, so take the cost of the final paths
+    let cost = forward.visited.cost(fi) + backward.visited.cost(bi);
+    let mut path = forward.visited.path(fi);
+    let mut back = backward.visited.path(bi);
+    back.pop();
+    path.extend(back.into_iter().rev());
+    Some((path, cost))
+}
+
+#[cfg(test)]
+fn grid_successors(n: &(i64, i64)) -> Vec<((i64, i64), i64)> {
+    // 10x10 grid where entering column 5 is expensive except in the bottom row
+    [(-1, 0), (1, 0), (0, -1), (0, 1)]
+        .iter()
+        .map(|(dx, dy)| (n.0 + dx, n.1 + dy))
+        .filter(|(x, y)| (0..10).contains(x) && (0..10).contains(y))
+        .map(|(x, y)| ((x, y), if x == 5 && y != 9 { 20 } else { 1 }))
+        .collect()
+}
+
+#[test]
+fn test_dijkstra_astar() {
+    let goal = (9, 0);
+    let (path, cost) = dijkstra(&(0, 0), grid_successors, |n| *n == goal).unwrap();
+    assert_eq!(cost, 27);
+    assert_eq!(path.first(), Some(&(0, 0)));
+    assert_eq!(path.last(), Some(&goal));
+    assert_eq!(path.len(), 28);
+
+    let manhattan = |n: &(i64, i64)| (goal.0 - n.0).abs() + (goal.1 - n.1).abs();
+    let (res_dijkstra, stats_dijkstra) =
+        astar_with_stats(&(0, 0), grid_successors, |_| 0, |n| *n == goal);
+    let (res_astar, stats_astar) =
+        astar_with_stats(&(0, 0), grid_successors, manhattan, |n| *n == goal);
+    assert_eq!(res_dijkstra.map(|(_, c)| c), Some(27));
+    assert_eq!(res_astar.map(|(_, c)| c), Some(27));
+    assert!(stats_astar.expanded <= stats_dijkstra.expanded);
+    assert!(stats_astar.queued >= stats_astar.expanded);
+
+    assert_eq!(dijkstra(&(0, 0), grid_successors, |n| n.0 > 10), None);
+}
+
+#[test]
+fn test_uniform_cost_search() {
+    let (came_from, stats) = uniform_cost_search(&(0, 0), grid_successors);
+    assert_eq!(came_from.len(), 100);
+    assert_eq!(stats.expanded, 100);
+    assert_eq!(came_from[&(0, 0)], (None, 0));
+    assert_eq!(came_from[&(9, 0)].1, 27);
+    assert_eq!(came_from[&(4, 4)].1, 8);
+
+    let path = reconstruct_path(&came_from, &(9, 0)).unwrap();
+    assert_eq!(path.len(), 28);
+    assert!(path
+        .windows(2)
+        .all(|w| grid_successors(&w[0]).iter().any(|(n, _)| *n == w[1])));
+    assert_eq!(reconstruct_path(&came_from, &(10, 10)), None);
+}
+
+#[test]
+fn test_ida_star() {
+    // 4x4 grid with varying entry costs
+    let successors = |n: &(i64, i64)| {
+        grid_successors(n)
+            .into_iter()
+            .filter(|((x, y), _)| *x < 4 && *y < 4)
+            .map(|((x, y), _)| ((x, y), (x * 7 + y * 3) % 5 + 1))
+            .collect::<Vec<_>>()
+    };
+    for goal in [(3, 3), (3, 0), (0, 2)] {
+        let manhattan = |n: &(i64, i64)| (goal.0 - n.0).abs() + (goal.1 - n.1).abs();
+        let (path, cost) = ida_star(&(0, 0), successors, manhattan, |n| *n == goal).unwrap();
+        let (_, ref_cost) = dijkstra(&(0, 0), successors, |n| *n == goal).unwrap();
+        assert_eq!(cost, ref_cost);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&goal));
+    }
+
+    // reach 37 from 1 by incrementing, decrementing or doubling
+    let successors = |n: &i64| [(n + 1, 1), (n - 1, 1), (n * 2, 1)];
+    let (path, cost) = ida_star(&1, successors, |_| 0, |n| *n == 37).unwrap();
+    assert_eq!(cost, 7);
+    assert_eq!(path, vec![1, 2, 4, 8, 9, 18, 36, 37]);
+
+    let count_down = |n: &i64| [(n - 1, 1)].into_iter().filter(|(n, _)| *n > -3);
+    assert_eq!(ida_star(&1, count_down, |_| 0, |n| *n == 2), None);
+}
+
+#[test]
+fn test_bidirectional_bfs() {
+    let neighbors = |n: &(i64, i64)| {
+        grid_successors(n)
+            .into_iter()
+            .map(|(n, _)| n)
+            .filter(|(x, y)| *x != 5 || *y == 9)
+            .collect::<Vec<_>>()
+    };
+    for goal in [(9, 0), (0, 0), (4, 7), (6, 9)] {
+        let path = bidirectional_bfs(&(0, 0), &goal, neighbors, neighbors).unwrap();
+        let (_, steps) = dijkstra(
+            &(0, 0),
+            |n| neighbors(n).into_iter().map(|n| (n, 1)),
+            |n| *n == goal,
+        )
+        .unwrap();
+        assert_eq!(path.len() as i64 - 1, steps);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&goal));
+        assert!(path.windows(2).all(|w| neighbors(&w[0]).contains(&w[1])));
+    }
+    assert_eq!(
+        bidirectional_bfs(&(0, 0), &(20, 0), neighbors, neighbors),
+        None
+    );
+}
+
+#[test]
+fn test_bidirectional_dijkstra() {
+    // every move into a node costs the same, what 'grid_successors' charges for entering it
+    let predecessors = |n: &(i64, i64)| {
+        let enter = |(x, y): (i64, i64)| if x == 5 && y != 9 { 20 } else { 1 };
+        let cost = enter(*n);
+        grid_successors(n)
+            .into_iter()
+            .map(move |(p, _)| (p, cost))
+            .collect::<Vec<_>>()
+    };
+    for goal in [(9, 0), (0, 0), (4, 7), (6, 9), (5, 5)] {
+        let (path, cost) =
+            bidirectional_dijkstra(&(0, 0), &goal, grid_successors, predecessors).unwrap();
+        let (_, ref_cost) = dijkstra(&(0, 0), grid_successors, |n| *n == goal).unwrap();
+        assert_eq!(cost, ref_cost);
+        assert_eq!(path.first(), Some(&(0, 0)));
+        assert_eq!(path.last(), Some(&goal));
+        let path_cost = path
+            .windows(2)
+            .map(|w| {
+                let moves = grid_successors(&w[0]);
+                moves.iter().find(|(n, _)| *n == w[1]).unwrap().1
+            })
+            .sum::<i64>();
+        assert_eq!(path_cost, cost);
+    }
+    assert_eq!(
+        bidirectional_dijkstra(&(0, 0), &(20, 0), grid_successors, predecessors),
+        None
+    );
+
+    // directed: reach 37 from 1 where incrementing costs 1 and doubling costs 3
+    let successors = |n: &i64| {
+        [(n + 1, 1), (n * 2, 3)]
+            .into_iter()
+            .filter(|(n, _)| *n <= 100)
+    };
+    let predecessors = |n: &i64| {
+        let halve = (n % 2 == 0).then_some((n / 2, 3));
+        [(n - 1, 1)]
+            .into_iter()
+            .filter(|(n, _)| *n >= 1)
+            .chain(halve)
+    };
+    let (path, cost) = bidirectional_dijkstra(&1, &37, successors, predecessors).unwrap();
+    let (_, ref_cost) = dijkstra(&1, successors, |n| *n == 37).unwrap();
+    assert_eq!(cost, ref_cost);
+    assert_eq!(path.first(), Some(&1));
+    assert_eq!(path.last(), Some(&37));
+}
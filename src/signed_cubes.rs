@@ -0,0 +1,127 @@
+//! Day 22 volumes by inclusion–exclusion: the set is a sum of boxes with signed multiplicities, so
+//! adding a box only needs one intersection per stored box instead of cutting every stored box into
+//! up to '2 * N' fragments. Works for boxes of any dimension, not just cubes.
+
+use std::collections::HashMap;
+
+use crate::boxn::BoxN;
+
+/// Same interface as 'CubeSet'. Each stored box counts 'multiplicity' times (possibly negative),
+/// and a point is on if the multiplicities of the boxes containing it add up to 1.
+#[derive(Debug, Clone, Default)]
+pub struct SignedCubeSet<const N: usize = 3> {
+    cubes: HashMap<BoxN<N>, i64>,
+}
+
+impl<const N: usize> SignedCubeSet<N> {
+    pub fn add(&mut self, add_cube: BoxN<N>, on_off: bool) {
+        // cancel whatever is already counted inside 'add_cube', then count it once if it is on
+        let mut update = HashMap::new();
+        for (cube, multiplicity) in &self.cubes {
+            if let Some(common) = cube.intersection(&add_cube) {
+                *update.entry(common).or_insert(0) -= multiplicity;
+            }
+        }
+        if on_off {
+            *update.entry(add_cube).or_insert(0) += 1;
+        }
+
+        for (cube, multiplicity) in update {
+            *self.cubes.entry(cube).or_insert(0) += multiplicity;
+        }
+        self.cubes.retain(|_, m| *m != 0);
+    }
+
+    pub fn volume(&self) -> i64 {
+        self.cubes.iter().map(|(c, m)| m * c.volume()).sum()
+    }
+
+    pub fn volume_intersect(&self, add_cube: &BoxN<N>) -> i64 {
+        self.cubes
+            .iter()
+            .filter_map(|(c, m)| c.intersection(add_cube).map(|i| m * i.volume()))
+            .sum()
+    }
+
+    /// How many boxes are stored.
+    pub fn len(&self) -> usize {
+        self.cubes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cubes.is_empty()
+    }
+}
+
+#[test]
+fn test_signed_cube_set() {
+    use crate::{Cube, CubeSet};
+
+    let mut signed = SignedCubeSet::default();
+    signed.add(Cube::new(0..=0, 0..=1, 0..=2), true);
+    assert_eq!(signed.volume(), 6);
+    signed.add(Cube::new(0..=0, 0..=0, 0..=0), false);
+    assert_eq!(signed.volume(), 5);
+    signed.add(Cube::new(0..=0, 0..=0, 0..=0), false);
+    assert_eq!(signed.volume(), 5);
+
+    let steps = [
+        (Cube::new(10..=12, 10..=12, 10..=12), true),
+        (Cube::new(11..=13, 11..=13, 11..=13), true),
+        (Cube::new(9..=11, 9..=11, 9..=11), false),
+        (Cube::new(10..=10, 10..=10, 10..=10), true),
+        (Cube::new(-5..=30, 12..=12, -5..=30), true),
+        (Cube::new(12..=12, -5..=30, 11..=11), false),
+    ];
+    let mut signed = SignedCubeSet::default();
+    let mut cube_set = CubeSet::default();
+    let region = Cube::new(11..=20, 0..=12, 10..=11);
+    for (cube, on_off) in steps {
+        signed.add(cube.clone(), on_off);
+        cube_set.add(cube, on_off);
+        assert_eq!(signed.volume(), cube_set.volume());
+        assert_eq!(
+            signed.volume_intersect(&region),
+            cube_set.volume_intersect(&region)
+        );
+    }
+}
+
+#[test]
+fn test_signed_reboot_2d_4d() {
+    use crate::parser::{lines_of, parse_all, reboot_step_with_axes, OnOff};
+
+    let input = "on y=0..9,x=0..9
+        off x=5..14,y=5..14
+        on x=8..8,y=-2..20
+        off y=3..3,x=-5..5";
+    let steps = parse_all(lines_of(reboot_step_with_axes(["x", "y"])), input).unwrap();
+    let mut set = SignedCubeSet::<2>::default();
+    for step in &steps {
+        set.add(step.boxn(), step.state == OnOff::On);
+    }
+    let lit = |x: i64, y: i64| {
+        steps
+            .iter()
+            .rev()
+            .find(|s| s.boxn().contains(&[x, y]))
+            .is_some_and(|s| s.state == OnOff::On)
+    };
+    let brute_force = (-10..=30)
+        .flat_map(|x| (-10..=30).map(move |y| (x, y)))
+        .filter(|&(x, y)| lit(x, y))
+        .count() as i64;
+    assert_eq!(set.volume(), brute_force);
+    assert_eq!(set.volume(), 100 - 25 + 18 - 6);
+    assert_eq!(set.volume_intersect(&BoxN::from([8..=8, 0..=20])), 21);
+
+    let input = "on x=0..2,y=0..2,z=0..2,w=0..2
+        off w=1..1,x=1..1,y=1..1,z=1..1
+        on x=2..3,y=2..3,z=2..3,w=2..3";
+    let steps = parse_all(lines_of(reboot_step_with_axes(["x", "y", "z", "w"])), input).unwrap();
+    let mut set = SignedCubeSet::<4>::default();
+    for step in &steps {
+        set.add(step.boxn(), step.state == OnOff::On);
+    }
+    assert_eq!(set.volume(), 81 - 1 + 16 - 1);
+}
@@ -0,0 +1,740 @@
+//! Snailfish numbers (day 18) as an arena of pairs with parent links, so that explode and split
+//! only touch the path between the changed pair and its neighbours.
+
+use std::{
+    cmp::Reverse,
+    fmt,
+    sync::{
+        atomic::{AtomicI64, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thread,
+};
+
+use crate::SfNumber;
+
+pub type SnId = usize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnDir {
+    Left,
+    Right,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SnLink {
+    Number(i64),
+    Node(SnId),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct SnNode {
+    pub parent: Option<SnId>,
+    pub left: SnLink,
+    pub right: SnLink,
+}
+
+/// A place in a pair that holds a regular number or another pair.
+type Slot = (SnId, SnDir);
+
+/// One step of a reduction. 'path' leads from the outermost pair to the exploding pair or to the
+/// regular number that is split.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnEvent {
+    Explode {
+        path: Vec<SnDir>,
+        left: i64,
+        right: i64,
+    },
+    Split {
+        path: Vec<SnDir>,
+        value: i64,
+    },
+}
+
+/// An event and the number right after it, in '[a,b]' notation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SnStep {
+    pub event: SnEvent,
+    pub number: String,
+}
+
+/// The knobs of snailfish arithmetic. Pairs nested inside 'explode_depth' pairs explode, regular
+/// numbers above 'split_above' split, and the magnitude of '[a,b]' is 'weights.0 * a + weights.1 *
+/// b'. Both 'explode_depth' and 'split_above' have to be at least 1, otherwise the outermost pair
+/// would explode or '1' would split into '[0,1]' forever.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SnailfishRules {
+    pub explode_depth: usize,
+    pub split_above: i64,
+    pub weights: (i64, i64),
+}
+
+/// The rules from the puzzle.
+impl Default for SnailfishRules {
+    fn default() -> Self {
+        SnailfishRules {
+            explode_depth: 4,
+            split_above: 9,
+            weights: (3, 2),
+        }
+    }
+}
+
+type Observer<'a> = Option<&'a mut dyn FnMut(&SnNodes, SnEvent)>;
+
+/// A snailfish number. Nodes freed by explosions are reused by splits.
+#[derive(Debug, Clone)]
+pub struct SnNodes {
+    nodes: Vec<SnNode>,
+    free: Vec<SnId>,
+    root: SnId,
+}
+
+/// 'SnNodes' always has a pair at the root, so a lone regular number can't be converted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotAPair(pub i64);
+
+impl fmt::Display for NotAPair {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "a snailfish number has to be a pair, not {}", self.0)
+    }
+}
+
+impl std::error::Error for NotAPair {}
+
+impl TryFrom<&SfNumber> for SnNodes {
+    type Error = NotAPair;
+
+    fn try_from(n: &SfNumber) -> Result<Self, Self::Error> {
+        match n {
+            SfNumber::Pair(l, r) => Ok(SnNodes::from_pair(l, r)),
+            SfNumber::Number(v) => Err(NotAPair(*v)),
+        }
+    }
+}
+
+impl fmt::Display for SnNodes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.fmt_link(SnLink::Node(self.root), f)
+    }
+}
+
+impl SnNodes {
+    /// The number '[left,right]'.
+    pub fn from_pair(left: &SfNumber, right: &SfNumber) -> SnNodes {
+        let mut nodes = SnNodes {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: 0,
+        };
+        nodes.root = nodes.put(SnNode {
+            parent: None,
+            left: SnLink::Number(0),
+            right: SnLink::Number(0),
+        });
+        nodes.nodes[nodes.root].left = nodes.link_from(left, Some(nodes.root));
+        nodes.nodes[nodes.root].right = nodes.link_from(right, Some(nodes.root));
+        nodes
+    }
+
+    fn fmt_link(&self, link: SnLink, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match link {
+            SnLink::Number(v) => write!(f, "{}", v),
+            SnLink::Node(id) => {
+                write!(f, "[")?;
+                self.fmt_link(self.nodes[id].left, f)?;
+                write!(f, ",")?;
+                self.fmt_link(self.nodes[id].right, f)?;
+                write!(f, "]")
+            }
+        }
+    }
+
+    fn link_from(&mut self, n: &SfNumber, parent: Option<SnId>) -> SnLink {
+        match n {
+            SfNumber::Number(v) => SnLink::Number(*v),
+            SfNumber::Pair(l, r) => {
+                let id = self.put(SnNode {
+                    parent,
+                    left: SnLink::Number(0),
+                    right: SnLink::Number(0),
+                });
+                self.nodes[id].left = self.link_from(l, Some(id));
+                self.nodes[id].right = self.link_from(r, Some(id));
+                SnLink::Node(id)
+            }
+        }
+    }
+
+    fn put(&mut self, node: SnNode) -> SnId {
+        if let Some(id) = self.free.pop() {
+            self.nodes[id] = node;
+            id
+        } else {
+            self.nodes.push(node);
+            self.nodes.len() - 1
+        }
+    }
+
+    pub fn to_sf(&self) -> SfNumber {
+        self.sf_from(SnLink::Node(self.root))
+    }
+
+    fn sf_from(&self, link: SnLink) -> SfNumber {
+        match link {
+            SnLink::Number(v) => SfNumber::Number(v),
+            SnLink::Node(id) => SfNumber::Pair(
+                Box::new(self.sf_from(self.nodes[id].left)),
+                Box::new(self.sf_from(self.nodes[id].right)),
+            ),
+        }
+    }
+
+    pub fn magnitude(&self) -> i64 {
+        self.magnitude_by(&SnailfishRules::default())
+    }
+
+    pub fn magnitude_by(&self, rules: &SnailfishRules) -> i64 {
+        self.magnitude_of(SnLink::Node(self.root), rules.weights)
+    }
+
+    fn magnitude_of(&self, link: SnLink, (wl, wr): (i64, i64)) -> i64 {
+        match link {
+            SnLink::Number(v) => v,
+            SnLink::Node(id) => {
+                wl * self.magnitude_of(self.nodes[id].left, (wl, wr))
+                    + wr * self.magnitude_of(self.nodes[id].right, (wl, wr))
+            }
+        }
+    }
+
+    /// The reduced sum of 'self' and 'other'.
+    pub fn add(&self, other: &SnNodes) -> SnNodes {
+        self.add_by(other, &SnailfishRules::default())
+    }
+
+    pub fn add_by(&self, other: &SnNodes, rules: &SnailfishRules) -> SnNodes {
+        let mut sum = SnNodes {
+            nodes: Vec::new(),
+            free: Vec::new(),
+            root: 0,
+        };
+        self.add_into(other, rules, &mut sum);
+        sum
+    }
+
+    /// Like 'add_by', but reuse the storage of 'sum', which is overwritten.
+    pub fn add_into(&self, other: &SnNodes, rules: &SnailfishRules, sum: &mut SnNodes) {
+        sum.nodes.clear();
+        sum.nodes.extend_from_slice(&self.nodes);
+        sum.free.clear();
+        sum.free.extend_from_slice(&self.free);
+        sum.root = self.root;
+
+        let offset = sum.nodes.len();
+        let shift = |link: SnLink| match link {
+            SnLink::Node(id) => SnLink::Node(id + offset),
+            number => number,
+        };
+        sum.nodes.extend(other.nodes.iter().map(|n| SnNode {
+            parent: n.parent.map(|p| p + offset),
+            left: shift(n.left),
+            right: shift(n.right),
+        }));
+        sum.free.extend(other.free.iter().map(|id| id + offset));
+
+        let (left, right) = (sum.root, other.root + offset);
+        sum.root = sum.put(SnNode {
+            parent: None,
+            left: SnLink::Node(left),
+            right: SnLink::Node(right),
+        });
+        sum.nodes[left].parent = Some(sum.root);
+        sum.nodes[right].parent = Some(sum.root);
+        sum.reduce_by(rules);
+    }
+
+    /// The regular numbers from left to right.
+    pub fn numbers(&self) -> Vec<i64> {
+        let mut numbers = Vec::new();
+        let mut cur = Some(self.outermost((self.root, SnDir::Left), SnDir::Left));
+        while let Some(slot) = cur {
+            if let SnLink::Number(n) = self.get(slot) {
+                numbers.push(n);
+            }
+            cur = self.neighbour_of_slot(slot, SnDir::Right);
+        }
+        numbers
+    }
+
+    fn get(&self, (id, dir): Slot) -> SnLink {
+        match dir {
+            SnDir::Left => self.nodes[id].left,
+            SnDir::Right => self.nodes[id].right,
+        }
+    }
+
+    fn set(&mut self, (id, dir): Slot, link: SnLink) {
+        match dir {
+            SnDir::Left => self.nodes[id].left = link,
+            SnDir::Right => self.nodes[id].right = link,
+        }
+    }
+
+    /// Where the pair 'id' hangs in its parent, None for the outermost pair.
+    fn slot_of(&self, id: SnId) -> Option<Slot> {
+        let parent = self.nodes[id].parent?;
+        if self.nodes[parent].left == SnLink::Node(id) {
+            Some((parent, SnDir::Left))
+        } else {
+            Some((parent, SnDir::Right))
+        }
+    }
+
+    fn path_to(&self, mut slot: Slot) -> Vec<SnDir> {
+        let mut path = vec![slot.1];
+        while let Some(up) = self.slot_of(slot.0) {
+            path.push(up.1);
+            slot = up;
+        }
+        path.reverse();
+        path
+    }
+
+    fn depth(&self, mut id: SnId) -> usize {
+        let mut depth = 0;
+        while let Some(p) = self.nodes[id].parent {
+            depth += 1;
+            id = p;
+        }
+        depth
+    }
+
+    /// Slot of the regular number at the far 'dir' end of the subtree in 'slot'.
+    fn outermost(&self, mut slot: Slot, dir: SnDir) -> Slot {
+        while let SnLink::Node(id) = self.get(slot) {
+            slot = (id, dir);
+        }
+        slot
+    }
+
+    /// The regular number right before (SnDir::Left) or after (SnDir::Right) the pair 'id'.
+    fn neighbour_of_node(&self, mut id: SnId, dir: SnDir) -> Option<Slot> {
+        let other = match dir {
+            SnDir::Left => SnDir::Right,
+            SnDir::Right => SnDir::Left,
+        };
+        while let Some(p) = self.nodes[id].parent {
+            if self.get((p, other)) == SnLink::Node(id) {
+                return Some(self.outermost((p, dir), other));
+            }
+            id = p;
+        }
+        None
+    }
+
+    /// The regular number before or after the one in 'slot'.
+    fn neighbour_of_slot(&self, (id, side): Slot, dir: SnDir) -> Option<Slot> {
+        match (side, dir) {
+            (SnDir::Right, SnDir::Left) => Some(self.outermost((id, SnDir::Left), SnDir::Right)),
+            (SnDir::Left, SnDir::Right) => Some(self.outermost((id, SnDir::Right), SnDir::Left)),
+            _ => self.neighbour_of_node(id, dir),
+        }
+    }
+
+    fn add_to(&mut self, slot: Option<Slot>, v: i64) {
+        if let Some(slot) = slot {
+            if let SnLink::Number(n) = self.get(slot) {
+                self.set(slot, SnLink::Number(n + v));
+            }
+        }
+    }
+
+    /// Explode the pair 'id' (which has to hold two regular numbers) and return the slot that now
+    /// holds the 0.
+    fn explode(&mut self, id: SnId, observer: &mut Observer) -> Slot {
+        let (SnLink::Number(left), SnLink::Number(right)) =
+            (self.nodes[id].left, self.nodes[id].right)
+        else {
+            panic!("bad nodes");
+        };
+        self.add_to(self.neighbour_of_node(id, SnDir::Left), left);
+        self.add_to(self.neighbour_of_node(id, SnDir::Right), right);
+
+        let slot = self.slot_of(id).expect("the outermost pair can't explode");
+        self.set(slot, SnLink::Number(0));
+        self.free.push(id);
+        if let Some(observer) = observer {
+            let path = self.path_to(slot);
+            observer(self, SnEvent::Explode { path, left, right });
+        }
+        slot
+    }
+
+    /// Explode the pairs inside the pair 'id' that are nested inside at least 'min_depth' pairs,
+    /// inner pairs before the pair holding them and otherwise from left to right.
+    fn explode_deep(&mut self, id: SnId, depth: usize, min_depth: usize, observer: &mut Observer) {
+        if let SnLink::Node(left) = self.nodes[id].left {
+            self.explode_deep(left, depth + 1, min_depth, observer);
+        }
+        if let SnLink::Node(right) = self.nodes[id].right {
+            self.explode_deep(right, depth + 1, min_depth, observer);
+        }
+        if depth >= min_depth {
+            self.explode(id, observer);
+        }
+    }
+
+    /// Explode and split until neither applies, always acting on the leftmost candidate, with
+    /// explosions before splits.
+    pub fn reduce(&mut self) {
+        self.reduce_by(&SnailfishRules::default());
+    }
+
+    pub fn reduce_by(&mut self, rules: &SnailfishRules) {
+        self.reduce_observed(rules, None);
+    }
+
+    /// Like 'reduce', and also return every explode and split in the order they happened.
+    pub fn reduce_with_trace(&mut self) -> Vec<SnStep> {
+        self.reduce_with_trace_by(&SnailfishRules::default())
+    }
+
+    pub fn reduce_with_trace_by(&mut self, rules: &SnailfishRules) -> Vec<SnStep> {
+        let mut steps = Vec::new();
+        self.reduce_observed(
+            rules,
+            Some(&mut |nodes: &SnNodes, event| {
+                steps.push(SnStep {
+                    event,
+                    number: nodes.to_string(),
+                })
+            }),
+        );
+        steps
+    }
+
+    fn reduce_observed(&mut self, rules: &SnailfishRules, mut observer: Observer) {
+        assert!(
+            rules.explode_depth > 0 && rules.split_above > 0,
+            "rules that never finish: {:?}",
+            rules
+        );
+
+        // explosions never create new pairs, and a pair turns into a pair of regular numbers
+        // right after the pairs inside it exploded, so one pass in that order handles all of them
+        self.explode_deep(self.root, 0, rules.explode_depth, &mut observer);
+
+        // now only splits can create pairs deep enough to explode, and those explode right away
+        let mut cur = Some(self.outermost((self.root, SnDir::Left), SnDir::Left));
+        while let Some(slot) = cur {
+            match self.get(slot) {
+                SnLink::Number(n) if n > rules.split_above => {
+                    let id = self.put(SnNode {
+                        parent: Some(slot.0),
+                        left: SnLink::Number(n / 2),
+                        right: SnLink::Number(n - n / 2),
+                    });
+                    self.set(slot, SnLink::Node(id));
+                    if let Some(observer) = &mut observer {
+                        let path = self.path_to(slot);
+                        observer(self, SnEvent::Split { path, value: n });
+                    }
+                    if self.depth(id) >= rules.explode_depth {
+                        let zero = self.explode(id, &mut observer);
+                        // the number to the left might have become too large
+                        cur = self.neighbour_of_slot(zero, SnDir::Left).or(Some(zero));
+                    } else {
+                        cur = Some((id, SnDir::Left));
+                    }
+                }
+                _ => cur = self.neighbour_of_slot(slot, SnDir::Right),
+            }
+        }
+    }
+}
+
+/// An upper bound on the magnitude of a reduced number whose regular numbers add up to at most
+/// 'total'. Explosions only lose some of the total and splits keep it, so a sum of two numbers
+/// can't have more than the two together. Reduced, the number fits into a full tree of
+/// 'explode_depth' levels of pairs, and the best it can do is to put as much as 'split_above' into
+/// each of the most heavily weighted places. None if there is no such bound because of negative
+/// weights or numbers.
+fn magnitude_bound(rules: &SnailfishRules, total: i64) -> Option<i64> {
+    let (wl, wr) = rules.weights;
+    if wl < 1 || wr < 1 || total < 0 || rules.explode_depth > 62 {
+        return None;
+    }
+    let depth = rules.explode_depth as u32;
+    // 'count' places of weight 'wl^k * wr^(depth-k)' each
+    let mut places = (0..=depth)
+        .map(|k| {
+            let count = (0..k).fold(1i128, |c, i| c * (depth - i) as i128 / (i + 1) as i128);
+            let weight = (wl as i128)
+                .checked_pow(k)
+                .and_then(|l| l.checked_mul((wr as i128).checked_pow(depth - k)?));
+            weight.map(|weight| (weight, count))
+        })
+        .collect::<Option<Vec<_>>>()?;
+    places.sort_by_key(|&(weight, _)| Reverse(weight));
+
+    let mut rest = total as i128;
+    let mut bound = 0i128;
+    for (weight, count) in places {
+        let filled = count.saturating_mul(rules.split_above as i128).min(rest);
+        bound = bound.checked_add(weight.checked_mul(filled)?)?;
+        rest -= filled;
+    }
+    i64::try_from(bound).ok()
+}
+
+/// The largest magnitude of the sum of two different numbers of 'numbers' (in either order), and
+/// the indices of the two numbers in the order they were added. Rows of pairs are shared out among
+/// 'threads' workers, and pairs that can't beat the best magnitude so far are skipped. Among pairs
+/// with the same magnitude the one with the smallest indices wins.
+pub fn max_pair_magnitude(
+    numbers: &[SnNodes],
+    rules: &SnailfishRules,
+    threads: usize,
+) -> Option<(i64, (usize, usize))> {
+    // heaviest numbers first, so that the bounds only go down along rows and columns (numbers
+    // without a total have no bound at all, they go first)
+    let mut order = numbers
+        .iter()
+        .enumerate()
+        .map(|(i, n)| {
+            let numbers = n.numbers();
+            let total = if numbers.iter().all(|&v| v >= 0) {
+                Some(numbers.iter().sum::<i64>())
+            } else {
+                None
+            };
+            (i, total)
+        })
+        .collect::<Vec<_>>();
+    order.sort_by_key(|&(_, total)| Reverse(total.unwrap_or(i64::MAX)));
+    let bound = |a: Option<i64>, b: Option<i64>| {
+        a.zip(b)
+            .and_then(|(a, b)| magnitude_bound(rules, a.saturating_add(b)))
+            .unwrap_or(i64::MAX)
+    };
+
+    let next_row = AtomicUsize::new(0);
+    let best_magnitude = AtomicI64::new(i64::MIN);
+    let best = Mutex::new(None::<(i64, (usize, usize))>);
+    thread::scope(|scope| {
+        for _ in 0..threads.max(1) {
+            scope.spawn(|| {
+                let mut sum = SnNodes {
+                    nodes: Vec::new(),
+                    free: Vec::new(),
+                    root: 0,
+                };
+                loop {
+                    let row = next_row.fetch_add(1, Ordering::Relaxed);
+                    let Some(&(i, total_i)) = order.get(row) else {
+                        break;
+                    };
+                    for &(j, total_j) in &order {
+                        if bound(total_i, total_j) < best_magnitude.load(Ordering::Relaxed) {
+                            break;
+                        }
+                        if i == j {
+                            continue;
+                        }
+                        numbers[i].add_into(&numbers[j], rules, &mut sum);
+                        let magnitude = sum.magnitude_by(rules);
+                        if best_magnitude.fetch_max(magnitude, Ordering::Relaxed) > magnitude {
+                            continue;
+                        }
+
+                        let mut best = best.lock().unwrap();
+                        let better = match *best {
+                            None => true,
+                            Some((m, pair)) => magnitude > m || (magnitude == m && (i, j) < pair),
+                        };
+                        if better {
+                            *best = Some((magnitude, (i, j)));
+                        }
+                    }
+                }
+            });
+        }
+    });
+    best.into_inner().unwrap()
+}
+
+#[cfg(test)]
+fn sn(s: &str) -> SnNodes {
+    SnNodes::try_from(&crate::parser::parse_all(crate::parser::snailfish_number, s).unwrap())
+        .unwrap()
+}
+
+#[test]
+fn test_not_a_pair() {
+    let seven = crate::parser::parse_all(crate::parser::snailfish_number, "7").unwrap();
+    assert_eq!(
+        SnNodes::try_from(&seven).map(|n| n.to_string()),
+        Err(NotAPair(7))
+    );
+    let pair = SfNumber::Pair(Box::new(seven.clone()), Box::new(seven));
+    assert_eq!(SnNodes::try_from(&pair).unwrap().to_string(), "[7,7]");
+}
+
+#[test]
+fn test_reduce() {
+    let mut n = sn("[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]");
+    n.reduce();
+    assert_eq!(n.to_sf().to_string(), "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]");
+
+    let sum = ["[1,1]", "[2,2]", "[3,3]", "[4,4]", "[5,5]", "[6,6]"]
+        .iter()
+        .map(|s| sn(s))
+        .reduce(|a, b| a.add(&b))
+        .unwrap();
+    assert_eq!(sum.to_sf().to_string(), "[[[[5,0],[7,4]],[5,5]],[6,6]]");
+
+    let sum = sn("[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]")
+        .add(&sn("[7,[[[3,7],[4,3]],[[6,3],[8,8]]]]"));
+    assert_eq!(
+        sum.to_sf().to_string(),
+        "[[[[4,0],[5,4]],[[7,7],[6,0]]],[[8,[7,7]],[[7,9],[5,0]]]]"
+    );
+}
+
+#[test]
+fn test_reduce_with_trace() {
+    use SnDir::{Left as L, Right as R};
+
+    let mut n = sn("[[[[[4,3],4],4],[7,[[8,4],9]]],[1,1]]");
+    let steps = n.reduce_with_trace();
+    let expected = [
+        (
+            SnEvent::Explode {
+                path: vec![L, L, L, L],
+                left: 4,
+                right: 3,
+            },
+            "[[[[0,7],4],[7,[[8,4],9]]],[1,1]]",
+        ),
+        (
+            SnEvent::Explode {
+                path: vec![L, R, R, L],
+                left: 8,
+                right: 4,
+            },
+            "[[[[0,7],4],[15,[0,13]]],[1,1]]",
+        ),
+        (
+            SnEvent::Split {
+                path: vec![L, R, L],
+                value: 15,
+            },
+            "[[[[0,7],4],[[7,8],[0,13]]],[1,1]]",
+        ),
+        (
+            SnEvent::Split {
+                path: vec![L, R, R, R],
+                value: 13,
+            },
+            "[[[[0,7],4],[[7,8],[0,[6,7]]]],[1,1]]",
+        ),
+        (
+            SnEvent::Explode {
+                path: vec![L, R, R, R],
+                left: 6,
+                right: 7,
+            },
+            "[[[[0,7],4],[[7,8],[6,0]]],[8,1]]",
+        ),
+    ];
+    assert_eq!(steps.len(), expected.len());
+    for (step, (event, number)) in steps.into_iter().zip(expected) {
+        assert_eq!(step.event, event);
+        assert_eq!(step.number, number);
+    }
+    assert!(n.reduce_with_trace().is_empty());
+}
+
+#[test]
+fn test_rules() {
+    let rules = SnailfishRules::default();
+    let (a, b) = (
+        sn("[[[0,[4,5]],[0,0]],[[[4,5],[2,6]],[9,5]]]"),
+        sn("[7,[[[3,7],[4,3]],[[6,3],[8,8]]]]"),
+    );
+    assert_eq!(a.add_by(&b, &rules).to_string(), a.add(&b).to_string());
+    assert_eq!(a.magnitude_by(&rules), a.magnitude());
+
+    let shallow = SnailfishRules {
+        explode_depth: 2,
+        split_above: 5,
+        weights: (1, 1),
+    };
+    let mut n = sn("[[[1,2],3],[4,[5,[6,7]]]]");
+    let steps = n.reduce_with_trace_by(&shallow);
+    assert_eq!(steps[3].number, "[[0,5],[[7,8],0]]");
+    assert_eq!(steps.len(), 17);
+    assert_eq!(n.to_string(), "[[3,4],[0,4]]");
+    assert_eq!(n.magnitude_by(&shallow), 11);
+    // no pair nested inside two pairs, no number above 5
+    assert!(n.reduce_with_trace_by(&shallow).is_empty());
+}
+
+#[test]
+fn test_max_pair_magnitude() {
+    let numbers = [
+        "[[[0,[5,8]],[[1,7],[9,6]]],[[4,[1,2]],[[1,4],2]]]",
+        "[[[5,[2,8]],4],[5,[[9,9],0]]]",
+        "[6,[[[6,2],[5,6]],[[7,6],[4,7]]]]",
+        "[[[6,[0,7]],[0,9]],[4,[9,[9,0]]]]",
+        "[[[7,[6,4]],[3,[1,3]]],[[[5,5],1],9]]",
+        "[[6,[[7,3],[3,2]]],[[[3,8],[5,7]],4]]",
+        "[[[[5,4],[7,7]],8],[[8,3],8]]",
+        "[[9,3],[[9,9],[6,[4,9]]]]",
+        "[[2,[[7,7],7]],[[5,8],[[9,3],[0,2]]]]",
+        "[[[[5,2],5],[8,[3,7]]],[[5,[7,5]],[4,4]]]",
+    ]
+    .map(sn);
+    let rules = SnailfishRules::default();
+    for threads in 1..4 {
+        assert_eq!(
+            max_pair_magnitude(&numbers, &rules, threads),
+            Some((3993, (8, 0)))
+        );
+    }
+    assert_eq!(max_pair_magnitude(&numbers[..1], &rules, 2), None);
+    assert_eq!(magnitude_bound(&rules, i64::MAX), Some(5625));
+
+    // the same as trying all pairs, also with other rules
+    let generated = crate::generators::snailfish_list(7, 12);
+    let numbers = generated.input.lines().map(sn).collect::<Vec<_>>();
+    let (magnitude, (i, j)) = max_pair_magnitude(&numbers, &rules, 3).unwrap();
+    assert_eq!(Some(magnitude), generated.part2);
+    assert_eq!(numbers[i].add(&numbers[j]).magnitude(), magnitude);
+
+    let rules = SnailfishRules {
+        explode_depth: 3,
+        split_above: 12,
+        weights: (2, 5),
+    };
+    let brute_force = (0..numbers.len())
+        .flat_map(|i| (0..numbers.len()).map(move |j| (i, j)))
+        .filter(|(i, j)| i != j)
+        .map(|(i, j)| numbers[i].add_by(&numbers[j], &rules).magnitude_by(&rules))
+        .max();
+    assert_eq!(
+        max_pair_magnitude(&numbers, &rules, 2).map(|(m, _)| m),
+        brute_force
+    );
+}
+
+#[test]
+fn test_magnitude() {
+    assert_eq!(sn("[[1,2],[[3,4],5]]").magnitude(), 143);
+    assert_eq!(
+        sn("[[[[8,7],[7,7]],[[8,6],[7,7]]],[[[0,7],[6,6]],[8,7]]]").magnitude(),
+        3488
+    );
+}